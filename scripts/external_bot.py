@@ -0,0 +1,75 @@
+#!/usr/bin/env python3
+"""Reference client for the naughts external bot protocol.
+
+Run it against the engine like this:
+
+    naughts batch "external:python3 scripts/external_bot.py" randombot --game naughts --size 100
+
+The engine writes one JSON message per line to stdin, and expects one JSON
+message per line on stdout in reply to each "move" message. Anything written to
+stderr is passed through to the console, so use it for debugging output.
+
+Messages from the engine:
+
+    {"type": "setup", "identity": "X", "other_identity": "O",
+     "game_info": {"input_count": 18, "output_count": 9}}
+    {"type": "move", "id": 1, "inputs": [...], "available_moves": [...]}
+    {"type": "game_over", "winner": "X", "scores": [49.0, -490.0], "forfeit": false}
+    {"type": "quit"}
+
+Reply to each "move" message with the same id:
+
+    {"id": 1, "move": 4}
+
+The same process plays every game in a batch, and gets a "setup" message at the
+start of each game. If a reply takes longer than the move time limit, the engine
+applies the --timeout action and ignores the late reply.
+"""
+
+import json
+import random
+import sys
+
+
+class Bot:
+    """Plays a random available move. Replace choose_move() with something smarter."""
+
+    def __init__(self):
+        self.identity = None
+        self.other_identity = None
+        self.game_info = None
+
+    def setup(self, message):
+        self.identity = message["identity"]
+        self.other_identity = message["other_identity"]
+        self.game_info = message["game_info"]
+
+    def choose_move(self, inputs, available_moves):
+        return random.choice(available_moves)
+
+    def game_over(self, message):
+        print("Game over: {}".format(message), file=sys.stderr)
+
+
+def main():
+    bot = Bot()
+    for line in sys.stdin:
+        line = line.strip()
+        if not line:
+            continue
+
+        message = json.loads(line)
+        msg_type = message.get("type")
+        if msg_type == "setup":
+            bot.setup(message)
+        elif msg_type == "move":
+            move = bot.choose_move(message["inputs"], message["available_moves"])
+            print(json.dumps({"id": message["id"], "move": move}), flush=True)
+        elif msg_type == "game_over":
+            bot.game_over(message)
+        elif msg_type == "quit":
+            break
+
+
+if __name__ == "__main__":
+    main()
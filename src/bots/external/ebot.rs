@@ -0,0 +1,352 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gamebase::GameInfo;
+use crate::engine::gameobject::GameObject;
+use crate::engine::gameplayer::{GamePlayer, PlayerData};
+use crate::engine::gameresult::GameResult;
+
+use log::{error, warn};
+use serde_json;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a move if no move time limit was specified.
+pub const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+/// A running external bot process.
+///
+/// Messages are exchanged as one JSON object per line. The engine sends:
+///
+///   {"type": "setup", "identity": "X", "other_identity": "O",
+///    "game_info": {"input_count": 18, "output_count": 9}}
+///   {"type": "move", "id": 1, "inputs": [...], "available_moves": [...]}
+///   {"type": "game_over", "winner": "X", "scores": [49.0, -490.0], "forfeit": false}
+///   {"type": "quit"}
+///
+/// The process must answer each "move" message with {"id": 1, "move": 4}, echoing the id.
+/// Answers with an old id (e.g. after a timeout) are ignored.
+///
+/// The same process plays every game in a batch, so it gets a "setup" message at the start of
+/// each game.
+struct ExternalProcess {
+    command: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    next_id: u64,
+    /// Set when the process has crashed or sent something invalid, so it can't be reused.
+    broken: bool,
+}
+
+/// Processes that finished a game, by command, ready for the next game.
+/// They are left running until the engine exits, when their input is closed.
+fn idle_processes() -> &'static Mutex<HashMap<String, Vec<ExternalProcess>>> {
+    static IDLE: OnceLock<Mutex<HashMap<String, Vec<ExternalProcess>>>> = OnceLock::new();
+    IDLE.get_or_init(Default::default)
+}
+
+impl ExternalProcess {
+    /// Reuse an idle process for the command if there is one, otherwise start a new one.
+    fn start(command: &str) -> Result<Self, String> {
+        while let Some(mut x) = ExternalProcess::take_idle(command) {
+            // Processes that have exited since their last game are dropped.
+            if let Ok(None) = x.child.try_wait() {
+                return Ok(x);
+            }
+        }
+        ExternalProcess::spawn(command)
+    }
+
+    fn take_idle(command: &str) -> Option<Self> {
+        idle_processes()
+            .lock()
+            .expect("External bot lock poisoned")
+            .get_mut(command)
+            .and_then(|x| x.pop())
+    }
+
+    fn spawn(command: &str) -> Result<Self, String> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("Error starting external bot '{}': {}", command, e))?;
+        let stdin = child.stdin.take().expect("Child stdin was not piped");
+        let stdout = child.stdout.take().expect("Child stdout was not piped");
+
+        // Read lines on a separate thread so that we can time out waiting for them.
+        let (sender, lines) = channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(x) => {
+                        if sender.send(x).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        Ok(ExternalProcess {
+            command: command.to_string(),
+            child,
+            stdin,
+            lines,
+            next_id: 1,
+            broken: false,
+        })
+    }
+
+    fn send(&mut self, message: &serde_json::Value) -> Result<(), String> {
+        writeln!(self.stdin, "{}", message)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("Error writing to external bot: {}", e))
+    }
+
+    /// Send the inputs for one move and wait for the answer.
+    /// Returns None if there was no answer before the timeout.
+    fn request_move(
+        &mut self,
+        inputs: &[f32],
+        available_moves: &[u32],
+        timeout: Duration,
+    ) -> Result<Option<u32>, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&serde_json::json!({
+            "type": "move",
+            "id": id,
+            "inputs": inputs,
+            "available_moves": available_moves
+        }))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(remaining) {
+                Ok(x) => x,
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(String::from("External bot exited unexpectedly"))
+                }
+            };
+
+            let value: serde_json::Value = serde_json::from_str(&line)
+                .map_err(|e| format!("Invalid JSON from external bot: {}: {}", e, line))?;
+            if value.get("id").and_then(|x| x.as_u64()) != Some(id) {
+                warn!("Ignoring stale message from external bot: {}", line);
+                continue;
+            }
+
+            return value
+                .get("move")
+                .and_then(|x| x.as_u64())
+                .map(|x| Some(x as u32))
+                .ok_or_else(|| format!("Missing move in message from external bot: {}", line));
+        }
+    }
+}
+
+/// Puts the process back in the idle list when the last bot using it is dropped,
+/// unless it is broken.
+struct SharedProcess(Option<ExternalProcess>);
+
+impl SharedProcess {
+    fn get(&mut self) -> &mut ExternalProcess {
+        self.0
+            .as_mut()
+            .expect("External bot process was already released")
+    }
+}
+
+impl Drop for SharedProcess {
+    fn drop(&mut self) {
+        if let Some(x) = self.0.take() {
+            if !x.broken {
+                if let Ok(mut idle) = idle_processes().lock() {
+                    idle.entry(x.command.clone()).or_default().push(x);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ExternalProcess {
+    fn drop(&mut self) {
+        let _ = self.send(&serde_json::json!({"type": "quit"}));
+
+        // Give the process a moment to exit cleanly before killing it.
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// A bot that runs as a separate program, communicating over stdin/stdout.
+///
+/// If the process can't be started, crashes or sends an invalid message, the bot is marked as
+/// failed and forfeits the game. If it doesn't answer in time, the engine applies the timeout
+/// action, as it does for a bot that exceeds the move time limit.
+#[derive(Clone)]
+pub struct ExternalBot {
+    player_data: PlayerData,
+    command: String,
+    game_info: GameInfo,
+    timeout: Duration,
+    /// Shared between clones, since the process itself can't be copied.
+    process: Option<Arc<Mutex<SharedProcess>>>,
+    failed: bool,
+    timed_out: bool,
+}
+
+impl ExternalBot {
+    /// Create a bot that runs the specified shell command.
+    /// A `timeout_ms` of 0 uses `DEFAULT_TIMEOUT_MS`.
+    pub fn new(game_info: &GameInfo, command: &str, timeout_ms: u64) -> Self {
+        let timeout_ms = if timeout_ms > 0 {
+            timeout_ms
+        } else {
+            DEFAULT_TIMEOUT_MS
+        };
+        let mut bot = ExternalBot {
+            player_data: PlayerData {
+                name: String::from("ExternalBot"),
+                ..Default::default()
+            },
+            command: command.to_string(),
+            game_info: game_info.clone(),
+            timeout: Duration::from_millis(timeout_ms),
+            process: None,
+            failed: false,
+            timed_out: false,
+        };
+
+        match ExternalProcess::start(command) {
+            Ok(x) => bot.process = Some(Arc::new(Mutex::new(SharedProcess(Some(x))))),
+            Err(x) => bot.fail(&x),
+        }
+        bot
+    }
+
+    fn fail(&mut self, message: &str) {
+        error!("{}: {}", self.command, message);
+        self.failed = true;
+        if let Some(ref x) = self.process {
+            x.lock().expect("External bot lock poisoned").get().broken = true;
+        }
+    }
+
+    fn send(&mut self, message: serde_json::Value) {
+        if self.failed {
+            return;
+        }
+
+        let result = match self.process {
+            Some(ref x) => x
+                .lock()
+                .expect("External bot lock poisoned")
+                .get()
+                .send(&message),
+            None => Err(String::from("External bot is not running")),
+        };
+        if let Err(x) = result {
+            self.fail(&x);
+        }
+    }
+}
+
+impl GameObject for ExternalBot {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({ "command": self.command })
+    }
+    fn from_json(&mut self, _data: &serde_json::Value) -> NaughtsResult<()> {
+        Ok(())
+    }
+}
+
+impl GamePlayer for ExternalBot {
+    fn clone_box(&self) -> Box<dyn GamePlayer> {
+        Box::new(self.clone())
+    }
+
+    fn get_data(&self) -> &PlayerData {
+        &self.player_data
+    }
+
+    fn get_data_mut(&mut self) -> &mut PlayerData {
+        &mut self.player_data
+    }
+
+    fn setup(&mut self, identity: char, other_identity: char) {
+        self.player_data.identity = identity;
+        self.player_data.other_identity = other_identity;
+        self.send(serde_json::json!({
+            "type": "setup",
+            "identity": identity.to_string(),
+            "other_identity": other_identity.to_string(),
+            "game_info": {
+                "input_count": self.game_info.input_count,
+                "output_count": self.game_info.output_count
+            }
+        }));
+    }
+
+    fn process(&mut self, inputs: Vec<f32>, available_moves: &[u32]) -> u32 {
+        self.timed_out = false;
+        if self.failed {
+            return available_moves[0];
+        }
+
+        let result = match self.process {
+            Some(ref x) => x
+                .lock()
+                .expect("External bot lock poisoned")
+                .get()
+                .request_move(&inputs, available_moves, self.timeout),
+            None => Err(String::from("External bot is not running")),
+        };
+        match result {
+            Ok(Some(x)) => x,
+            Ok(None) => {
+                self.timed_out = true;
+                available_moves[0]
+            }
+            Err(x) => {
+                self.fail(&x);
+                available_moves[0]
+            }
+        }
+    }
+
+    fn game_over(&mut self, result: &GameResult) {
+        self.send(serde_json::json!({
+            "type": "game_over",
+            "winner": result.get_winner_identity().map(|x| x.to_string()),
+            "scores": [result.get_score1(), result.get_score2()],
+            "forfeit": result.is_forfeit()
+        }));
+    }
+
+    fn has_failed(&self) -> bool {
+        self.failed
+    }
+
+    fn has_timed_out(&self) -> bool {
+        self.timed_out
+    }
+}
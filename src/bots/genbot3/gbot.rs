@@ -1,28 +1,60 @@
-use crate::bots::genbot3::nodes::{get_node_instance, get_random_node_instance, Node, NodeOutput};
+use crate::bots::genbot3::nodes::{
+    get_node_instance, get_random_node_instance, Node, NodeOutput, NodeType,
+};
+use crate::engine::errors::{NaughtsError, NaughtsResult};
 use crate::engine::gamebase::GameInfo;
-use crate::engine::gameobject::GameObject;
+use crate::engine::gameobject::{check_version, GameObject};
 use crate::engine::gameplayer::{GamePlayer, PlayerData};
 
 use rand::seq::SliceRandom;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Options for new GenBot3 recipes. These have no effect on recipes loaded from JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenBot3Config {
+    /// The number of logic nodes, not including input nodes.
+    pub num_nodes: u32,
+    /// The number of nodes connected to each output node.
+    pub num_output_inputs: u32,
+}
+
+impl Default for GenBot3Config {
+    fn default() -> Self {
+        GenBot3Config {
+            num_nodes: 100,
+            num_output_inputs: 20,
+        }
+    }
+}
+
+/// The latest GenBot3 recipe version.
+pub const RECIPE_VERSION: u64 = 1;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct GenBot3 {
     player_data: PlayerData,
+    game_info: GameInfo,
     nodes: Vec<Node>,
     output_nodes: Vec<NodeOutput>,
 }
 
 impl GenBot3 {
     pub fn new(game_info: &GameInfo) -> Self {
+        GenBot3::with_config(game_info, &GenBot3Config::default())
+    }
+
+    pub fn with_config(game_info: &GameInfo, config: &GenBot3Config) -> Self {
         let mut data = PlayerData::default();
         data.name = String::from("GenBot3");
         let mut obj = GenBot3 {
             player_data: data,
-            nodes: Vec::with_capacity(100),
+            game_info: game_info.clone(),
+            nodes: Vec::with_capacity(config.num_nodes as usize),
             output_nodes: Vec::with_capacity(game_info.output_count as usize),
         };
-        obj.create(game_info);
+        obj.create(game_info, config);
         obj
     }
 
@@ -49,61 +81,141 @@ impl GenBot3 {
         recipe_blocks.join(",")
     }
 
-    fn create_from_recipe(&mut self, recipe: &str) {
-        self.nodes = Vec::with_capacity(100);
-        self.output_nodes = Vec::with_capacity(20);
+    /// Load a recipe, checking it against the game info before replacing the current nodes.
+    ///
+    /// The first input_count nodes must be input nodes, and each logic node may only use
+    /// earlier nodes as inputs. The output nodes come last, one per game output.
+    fn create_from_recipe(&mut self, recipe: &str) -> NaughtsResult<()> {
+        let invalid = |msg: String| NaughtsError::RecipeParse(format!("GenBot3 recipe: {}", msg));
+        let input_count = self.game_info.input_count as usize;
+        let output_count = self.game_info.output_count as usize;
+        let mut nodes = Vec::with_capacity(100);
+        let mut output_nodes = Vec::with_capacity(output_count);
+
+        for recipe_block in recipe.split(',') {
+            let mut ingredient_blocks = recipe_block.split(':');
+            let class_name = ingredient_blocks.next().unwrap_or_default();
+            let mut input_indexes = Vec::new();
+            for input_number in ingredient_blocks {
+                let index: usize = input_number.parse().map_err(|_| {
+                    invalid(format!(
+                        "invalid input index '{}' in '{}'",
+                        input_number, recipe_block
+                    ))
+                })?;
+                input_indexes.push(index);
+            }
 
-        let mut node_index = 0;
-        let recipe_blocks = recipe.split(',');
-        for recipe_block in recipe_blocks {
-            let ingredient_blocks: Vec<&str> = recipe_block.split(':').collect();
-            let class_name = ingredient_blocks[0];
             if class_name == "NODE_OUTPUT" {
+                let output_index = output_nodes.len();
+                if input_indexes.is_empty() {
+                    return Err(invalid(format!("output {} has no inputs", output_index)));
+                }
+                if let Some(x) = input_indexes.iter().find(|x| **x >= nodes.len()) {
+                    return Err(invalid(format!(
+                        "output {} uses node {}, but there are only {} nodes",
+                        output_index,
+                        x,
+                        nodes.len()
+                    )));
+                }
+
                 let mut instance = NodeOutput::default();
-                for input_number in &ingredient_blocks[1..] {
-                    let index: usize = input_number.parse().expect("Couldn't parse ingredient");
+                for index in input_indexes {
                     instance.add_input_node(index);
                 }
-                self.output_nodes.push(instance);
+                output_nodes.push(instance);
+                continue;
+            }
+
+            let node_index = nodes.len();
+            if !output_nodes.is_empty() {
+                return Err(invalid(format!(
+                    "node {} ({}) comes after an output node",
+                    node_index, class_name
+                )));
+            }
+
+            let mut instance = get_node_instance(class_name)?;
+            if node_index < input_count {
+                if class_name != "NODE_INPUT" || !input_indexes.is_empty() {
+                    return Err(invalid(format!(
+                        "node {} is '{}', but the first {} nodes must be NODE_INPUT",
+                        node_index, recipe_block, input_count
+                    )));
+                }
             } else {
-                let mut instance = get_node_instance(class_name);
-                if class_name != "NODE_INPUT" {
-                    for input_number in &ingredient_blocks[1..] {
-                        let index: usize = input_number.parse().expect("Couldn't parse ingredient");
-                        instance.add_input_node(index);
-                    }
+                if class_name == "NODE_INPUT" {
+                    return Err(invalid(format!(
+                        "node {} is NODE_INPUT, but the game only has {} inputs",
+                        node_index, input_count
+                    )));
+                }
+                if input_indexes.len() != instance.num_inputs {
+                    return Err(invalid(format!(
+                        "node {} ({}) has {} inputs, expected {}",
+                        node_index,
+                        class_name,
+                        input_indexes.len(),
+                        instance.num_inputs
+                    )));
+                }
+                if let Some(x) = input_indexes.iter().find(|x| **x >= node_index) {
+                    return Err(invalid(format!(
+                        "node {} ({}) uses node {}, but nodes can only use earlier nodes",
+                        node_index, class_name, x
+                    )));
+                }
+
+                for index in input_indexes {
+                    instance.add_input_node(index);
                 }
-                instance.index = node_index;
-                self.nodes.push(instance);
-                node_index += 1;
             }
+            instance.index = node_index;
+            nodes.push(instance);
+        }
+
+        if nodes.len() <= input_count {
+            return Err(invalid(format!(
+                "expected {} input nodes followed by at least 1 logic node, found {} nodes",
+                input_count,
+                nodes.len()
+            )));
         }
+        if output_nodes.len() != output_count {
+            return Err(invalid(format!(
+                "found {} outputs, but the game has {}",
+                output_nodes.len(),
+                output_count
+            )));
+        }
+
+        self.nodes = nodes;
+        self.output_nodes = output_nodes;
+        Ok(())
     }
 
-    fn create(&mut self, game_info: &GameInfo) {
-        self.nodes = Vec::with_capacity(100);
+    fn create(&mut self, game_info: &GameInfo, config: &GenBot3Config) {
+        self.nodes = Vec::with_capacity((game_info.input_count + config.num_nodes) as usize);
         self.output_nodes = Vec::with_capacity(game_info.output_count as usize);
 
         for n in 0..game_info.input_count {
-            let mut instance = get_node_instance("NODE_INPUT");
+            let mut instance = Node::new_typed(NodeType::Input);
             instance.index = n as usize;
             self.nodes.push(instance);
         }
 
-        let num_nodes = 100;
-        let mut next_index = self.nodes.len();
-        for _ in 0..num_nodes {
+        for _ in 0..config.num_nodes {
             let mut instance = get_random_node_instance();
-            instance.index = next_index;
+            instance.index = self.nodes.len();
             let indexes: Vec<usize> = (0..self.nodes.len()).collect();
             for index in indexes.choose_multiple(&mut rand::thread_rng(), instance.num_inputs) {
                 instance.add_input_node(*index);
             }
             self.nodes.push(instance);
-            next_index += 1;
         }
 
-        let num_inputs = 20;
+        let num_inputs = config.num_output_inputs as usize;
         for _ in 0..game_info.output_count {
             let mut instance = NodeOutput::default();
             let indexes: Vec<usize> = (0..self.nodes.len()).collect();
@@ -141,24 +253,35 @@ impl GenBot3 {
 impl GameObject for GenBot3 {
     fn to_json(&self) -> serde_json::Value {
         serde_json::json!({
+          "version": RECIPE_VERSION,
           "recipe": self.get_recipe()
         })
     }
 
-    fn from_json(&mut self, data: &serde_json::Value) {
-        if let Some(serde_json::Value::String(ref v)) = data.get("recipe") {
-            if !v.is_empty() {
-                self.create_from_recipe(v);
-            }
+    fn from_json(&mut self, data: &serde_json::Value) -> NaughtsResult<()> {
+        check_version(data, "GenBot3 recipe", RECIPE_VERSION)?;
+        match data.get("recipe") {
+            Some(serde_json::Value::String(ref v)) => self.create_from_recipe(v),
+            _ => Err(NaughtsError::RecipeParse(String::from(
+                "GenBot3 recipe must contain a \"recipe\" string",
+            ))),
         }
     }
 }
 
 impl GamePlayer for GenBot3 {
+    fn clone_box(&self) -> Box<dyn GamePlayer> {
+        Box::new(self.clone())
+    }
+
     fn is_genetic(&self) -> bool {
         true
     }
 
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
     fn get_data(&self) -> &PlayerData {
         &self.player_data
     }
@@ -177,13 +300,13 @@ impl GamePlayer for GenBot3 {
         // ENGAGE BRAIN.
         let end = self.nodes.len();
         for index in start..end {
-            self.nodes[index].update(&outputs.as_slice());
+            self.nodes[index].update(outputs.as_slice());
             outputs.push(self.nodes[index].output);
         }
 
         let mut outputs_final = Vec::with_capacity(self.output_nodes.len());
         for node in &mut self.output_nodes {
-            node.update(&outputs.as_slice());
+            node.update(outputs.as_slice());
             outputs_final.push(node.output);
         }
 
@@ -247,9 +370,42 @@ mod tests {
         let b = GenBot3::new(&game_info);
         let state1 = b.to_json();
         let mut b2 = GenBot3::new(&game_info);
-        b2.from_json(&state1);
+        b2.from_json(&state1).unwrap();
 
         assert_eq!(b.to_json(), state1, "State was exported the same twice");
         assert_eq!(b2.to_json(), state1, "State was imported correctly");
     }
+
+    #[test]
+    fn test_invalid_recipes() {
+        let game_info = GameInfo {
+            input_count: 2,
+            output_count: 1,
+        };
+        let mut b = GenBot3::new(&game_info);
+        let load =
+            |b: &mut GenBot3, recipe: &str| b.from_json(&serde_json::json!({ "recipe": recipe }));
+
+        load(&mut b, "NODE_INPUT,NODE_INPUT,NODE_AND:0:1,NODE_OUTPUT:2").unwrap();
+        let state = b.to_json();
+        let bad_recipes = [
+            "NODE_INPUT,NODE_INPUT,NODE_AND:0:3,NODE_NOT:0,NODE_OUTPUT:2",
+            "NODE_INPUT,NODE_INPUT,NODE_AND:0:1,NODE_OUTPUT:2,NODE_OUTPUT:2",
+            "NODE_INPUT,NODE_AND:0:0,NODE_NOT:1,NODE_OUTPUT:2",
+            "NODE_INPUT,NODE_INPUT,NODE_NOT:0:1,NODE_OUTPUT:2",
+            "NODE_INPUT,NODE_INPUT,NODE_AND:0:1,NODE_OUTPUT:3",
+        ];
+        for recipe in bad_recipes.iter() {
+            let err = load(&mut b, recipe).expect_err(recipe);
+            assert!(matches!(err, NaughtsError::RecipeParse(_)), "{}", err);
+            assert_eq!(b.to_json(), state, "Bad recipe left the bot unchanged");
+        }
+
+        let err = b
+            .from_json(&serde_json::json!({ "version": 2, "recipe": "" }))
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unsupported GenBot3 recipe version 2"));
+    }
 }
@@ -1,5 +1,8 @@
 use rand::seq::SliceRandom;
 
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+
+#[derive(Clone)]
 pub enum NodeType {
     Input,
     Not,
@@ -12,6 +15,7 @@ pub enum NodeType {
 }
 
 // TODO: instead of input nodes, have input indexes. This will make the code much simpler!.
+#[derive(Clone)]
 pub struct Node {
     pub node_type: NodeType,
     pub num_inputs: usize,
@@ -86,6 +90,7 @@ impl Node {
     }
 }
 
+#[derive(Clone)]
 pub struct NodeOutput {
     pub num_inputs: usize,
     pub input_indexes: Vec<usize>,
@@ -123,33 +128,39 @@ impl NodeOutput {
     }
 }
 
-pub fn get_node_instance(class_name: &str) -> Node {
-    match class_name {
-        "NODE_INPUT" => Node::new_typed(NodeType::Input),
-        "NODE_NOT" => Node::new_typed(NodeType::Not),
-        "NODE_AND" => Node::new_typed(NodeType::And),
-        "NODE_OR" => Node::new_typed(NodeType::Or),
-        "NODE_XOR" => Node::new_typed(NodeType::Xor),
-        "NODE_NAND" => Node::new_typed(NodeType::Nand),
-        "NODE_NOR" => Node::new_typed(NodeType::Nor),
-        "NODE_XNOR" => Node::new_typed(NodeType::Xnor),
-        "NODE_OUTPUT" => panic!("Can't use get_node_instance() on output node"),
-        _ => panic!(format!("Invalid class name: {}", class_name)),
-    }
+/// Create a node from its recipe class name.
+pub fn get_node_instance(class_name: &str) -> NaughtsResult<Node> {
+    let node_type = match class_name {
+        "NODE_INPUT" => NodeType::Input,
+        "NODE_NOT" => NodeType::Not,
+        "NODE_AND" => NodeType::And,
+        "NODE_OR" => NodeType::Or,
+        "NODE_XOR" => NodeType::Xor,
+        "NODE_NAND" => NodeType::Nand,
+        "NODE_NOR" => NodeType::Nor,
+        "NODE_XNOR" => NodeType::Xnor,
+        _ => {
+            return Err(NaughtsError::RecipeParse(format!(
+                "Invalid node class name: {}",
+                class_name
+            )))
+        }
+    };
+    Ok(Node::new_typed(node_type))
 }
 
 pub fn get_random_node_instance() -> Node {
     let node_pool = [
-        "NODE_NOT",
-        "NODE_AND",
-        "NODE_OR",
-        "NODE_XOR",
-        "NODE_NAND",
-        "NODE_NOR",
-        "NODE_XNOR",
+        NodeType::Not,
+        NodeType::And,
+        NodeType::Or,
+        NodeType::Xor,
+        NodeType::Nand,
+        NodeType::Nor,
+        NodeType::Xnor,
     ];
-    let class_name = node_pool
+    let node_type = node_pool
         .choose(&mut rand::thread_rng())
         .expect("Bad choice!");
-    get_node_instance(class_name)
+    Node::new_typed(node_type.clone())
 }
@@ -1,28 +1,58 @@
 use crate::bots::nbot1::neurons::{sigmoid, NeuronLayer};
 
+use crate::engine::errors::{NaughtsError, NaughtsResult};
 use crate::engine::gamebase::GameInfo;
-use crate::engine::gameobject::GameObject;
+use crate::engine::gameobject::{check_version, GameObject};
 use crate::engine::gameplayer::{GamePlayer, PlayerData};
 
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Default)]
+/// Options for NBot1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NBot1Config {
+    /// Mutation adds a random value in the range (-mutation_range, mutation_range)
+    /// to one weight or bias, before applying the sigmoid function.
+    pub mutation_range: f32,
+}
+
+impl Default for NBot1Config {
+    fn default() -> Self {
+        NBot1Config {
+            mutation_range: 2.0,
+        }
+    }
+}
+
+/// The latest NBot1 recipe version.
+pub const RECIPE_VERSION: u64 = 1;
+
+#[derive(Default, Clone)]
 pub struct NBot1 {
     player_data: PlayerData,
+    game_info: GameInfo,
     layers: Vec<NeuronLayer>,
     nodes_per_layer: usize,
     num_layers: usize,
+    config: NBot1Config,
 }
 
 impl NBot1 {
     pub fn new(game_info: &GameInfo) -> Self {
+        NBot1::with_config(game_info, &NBot1Config::default())
+    }
+
+    pub fn with_config(game_info: &GameInfo, config: &NBot1Config) -> Self {
         let mut data = PlayerData::default();
         data.name = String::from("NBot1");
         let mut obj = NBot1 {
             player_data: data,
+            game_info: game_info.clone(),
             layers: Vec::new(),
             nodes_per_layer: game_info.input_count as usize,
             num_layers: 3,
+            config: config.clone(),
         };
         obj.create(game_info);
         obj
@@ -44,34 +74,77 @@ impl NBot1 {
 impl GameObject for NBot1 {
     fn to_json(&self) -> serde_json::Value {
         let values: Vec<serde_json::Value> = self.layers.iter().map(|x| x.to_json()).collect();
-        serde_json::json!({ "layers": values })
+        serde_json::json!({ "version": RECIPE_VERSION, "layers": values })
     }
 
-    fn from_json(&mut self, data: &serde_json::Value) {
-        if let Some(serde_json::Value::Array(ref v)) = data.get("layers") {
-            if !v.is_empty() {
-                self.layers.clear();
-                for s in v {
-                    let mut layer = NeuronLayer::default();
-                    layer.from_json(s);
-                    self.layers.push(layer);
-                }
+    /// Load layers, checking that each layer has one weight per neuron in the layer before it
+    /// (or per game input), and that the last layer has one neuron per game output.
+    fn from_json(&mut self, data: &serde_json::Value) -> NaughtsResult<()> {
+        check_version(data, "NBot1 recipe", RECIPE_VERSION)?;
+        let invalid = |msg: String| NaughtsError::RecipeParse(format!("NBot1 recipe: {}", msg));
+        let values = match data.get("layers") {
+            Some(serde_json::Value::Array(ref v)) if !v.is_empty() => v,
+            _ => {
+                return Err(invalid(String::from(
+                    "expected a non-empty \"layers\" array",
+                )))
+            }
+        };
 
-                self.num_layers = self.layers.len();
-                self.nodes_per_layer = 0;
-                if !self.layers.is_empty() {
-                    self.nodes_per_layer = self.layers[0].nodes.len();
+        let mut layers = Vec::with_capacity(values.len());
+        let mut prev_layer_nodes = self.game_info.input_count as usize;
+        for (layer_index, value) in values.iter().enumerate() {
+            let mut layer = NeuronLayer::default();
+            layer.from_json(value).map_err(|e| match e {
+                NaughtsError::RecipeParse(x) => invalid(format!("layer {}: {}", layer_index, x)),
+                x => x,
+            })?;
+            if layer.nodes.is_empty() {
+                return Err(invalid(format!("layer {} has no neurons", layer_index)));
+            }
+
+            for (node_index, node) in layer.nodes.iter().enumerate() {
+                if node.input_weights.len() != prev_layer_nodes {
+                    return Err(invalid(format!(
+                        "layer {} neuron {} has {} weights, expected {}",
+                        layer_index,
+                        node_index,
+                        node.input_weights.len(),
+                        prev_layer_nodes
+                    )));
                 }
             }
+            prev_layer_nodes = layer.nodes.len();
+            layers.push(layer);
         }
+
+        if prev_layer_nodes != self.game_info.output_count as usize {
+            return Err(invalid(format!(
+                "the last layer has {} neurons, but the game has {} outputs",
+                prev_layer_nodes, self.game_info.output_count
+            )));
+        }
+
+        self.nodes_per_layer = layers[0].nodes.len();
+        self.num_layers = layers.len();
+        self.layers = layers;
+        Ok(())
     }
 }
 
 impl GamePlayer for NBot1 {
+    fn clone_box(&self) -> Box<dyn GamePlayer> {
+        Box::new(self.clone())
+    }
+
     fn is_genetic(&self) -> bool {
         true
     }
 
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
     fn get_data(&self) -> &PlayerData {
         &self.player_data
     }
@@ -100,6 +173,7 @@ impl GamePlayer for NBot1 {
     }
 
     fn mutate(&mut self) {
+        let range = self.config.mutation_range;
         let mut rng = rand::thread_rng();
         let layer_index = rng.gen_range(0, self.layers.len());
         let node_index = rng.gen_range(0, self.layers[layer_index].nodes.len());
@@ -112,11 +186,11 @@ impl GamePlayer for NBot1 {
             );
             let weight = self.layers[layer_index].nodes[node_index].input_weights[i];
             self.layers[layer_index].nodes[node_index].input_weights[i] =
-                sigmoid(weight + rng.gen_range(-2.0, 2.0));
+                sigmoid(weight + rng.gen_range(-range, range));
         } else {
             let bias = self.layers[layer_index].nodes[node_index].bias;
             self.layers[layer_index].nodes[node_index].bias =
-                sigmoid(bias + rng.gen_range(-2.0, 2.0));
+                sigmoid(bias + rng.gen_range(-range, range));
         }
     }
 }
@@ -134,7 +208,7 @@ mod tests {
         let state1 = b.to_json();
         println!("State1 = {}", state1);
         let mut b2 = NBot1::new(&game_info);
-        b2.from_json(&state1);
+        b2.from_json(&state1).unwrap();
 
         assert_eq!(b.to_json(), state1, "State was exported the same twice");
         assert_eq!(b2.to_json(), state1, "State was imported correctly");
@@ -145,4 +219,40 @@ mod tests {
         );
         assert_eq!(b.num_layers, b2.num_layers, "Num layers is same");
     }
+
+    #[test]
+    fn test_invalid_recipes() {
+        let game_info = GameInfo {
+            input_count: 2,
+            output_count: 1,
+        };
+        let mut b = NBot1::new(&game_info);
+        let state = b.to_json();
+        let neuron = |weights: usize| serde_json::json!({ "input_weights": vec![0.5; weights], "bias": 0.1 });
+        let bad_recipes = [
+            serde_json::json!({}),
+            serde_json::json!({ "layers": [] }),
+            serde_json::json!({ "layers": [{ "nodes": [neuron(3)] }] }),
+            serde_json::json!({ "layers": [{ "nodes": [neuron(2), neuron(2)] }] }),
+            serde_json::json!({ "layers": [{ "nodes": [neuron(2)] }, { "nodes": [neuron(2)] }] }),
+            serde_json::json!({ "layers": [{ "nodes": [{ "bias": 0.1 }] }] }),
+            serde_json::json!({ "layers": [{ "nodes": [] }] }),
+        ];
+        for recipe in bad_recipes.iter() {
+            let err = b.from_json(recipe).expect_err(&recipe.to_string());
+            assert!(matches!(err, NaughtsError::RecipeParse(_)), "{}", err);
+            assert_eq!(b.to_json(), state, "Bad recipe left the bot unchanged");
+        }
+
+        let err = b
+            .from_json(&serde_json::json!({ "version": 2, "layers": [] }))
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unsupported NBot1 recipe version 2"));
+
+        b.from_json(&serde_json::json!({ "layers": [{ "nodes": [neuron(2)] }] }))
+            .unwrap();
+        assert_eq!(b.num_layers, 1);
+    }
 }
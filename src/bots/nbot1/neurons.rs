@@ -2,6 +2,7 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_json;
 
+use crate::engine::errors::{NaughtsError, NaughtsResult};
 use crate::engine::gameobject::GameObject;
 
 pub fn sigmoid(a: f32) -> f32 {
@@ -44,14 +45,16 @@ impl GameObject for Neuron {
         })
         .unwrap_or_else(|_| serde_json::json!({}))
     }
-    fn from_json(&mut self, data: &serde_json::Value) {
-        let n: Neuron = serde_json::from_value(data.clone()).unwrap_or_else(|_| Neuron::default());
+    fn from_json(&mut self, data: &serde_json::Value) -> NaughtsResult<()> {
+        let n: Neuron = serde_json::from_value(data.clone())
+            .map_err(|e| NaughtsError::RecipeParse(format!("Invalid neuron: {}", e)))?;
         self.input_weights = n.input_weights;
         self.bias = n.bias;
+        Ok(())
     }
 }
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct NeuronLayer {
     pub nodes: Vec<Neuron>,
 }
@@ -82,9 +85,10 @@ impl GameObject for NeuronLayer {
         })
         .unwrap_or_else(|_| serde_json::json!([]))
     }
-    fn from_json(&mut self, data: &serde_json::Value) {
-        let n: NeuronLayer =
-            serde_json::from_value(data.clone()).unwrap_or_else(|_| NeuronLayer::default());
+    fn from_json(&mut self, data: &serde_json::Value) -> NaughtsResult<()> {
+        let n: NeuronLayer = serde_json::from_value(data.clone())
+            .map_err(|e| NaughtsError::RecipeParse(format!("Invalid neuron layer: {}", e)))?;
         self.nodes = n.nodes;
+        Ok(())
     }
 }
\ No newline at end of file
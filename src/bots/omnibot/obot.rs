@@ -1,9 +1,10 @@
+use crate::engine::errors::NaughtsResult;
 use crate::engine::gamebase::GameInfo;
 use crate::engine::gameobject::GameObject;
 use crate::engine::gameplayer::{GamePlayer, PlayerData};
 use serde_json;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct OmniBot {
     player_data: PlayerData,
 }
@@ -21,10 +22,16 @@ impl GameObject for OmniBot {
     fn to_json(&self) -> serde_json::Value {
         serde_json::json!({})
     }
-    fn from_json(&mut self, _data: &serde_json::Value) {}
+    fn from_json(&mut self, _data: &serde_json::Value) -> NaughtsResult<()> {
+        Ok(())
+    }
 }
 
 impl GamePlayer for OmniBot {
+    fn clone_box(&self) -> Box<dyn GamePlayer> {
+        Box::new(self.clone())
+    }
+
     fn get_data(&self) -> &PlayerData {
         &self.player_data
     }
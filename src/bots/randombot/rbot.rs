@@ -1,10 +1,11 @@
+use crate::engine::errors::NaughtsResult;
 use crate::engine::gamebase::GameInfo;
 use crate::engine::gameobject::GameObject;
 use crate::engine::gameplayer::{GamePlayer, PlayerData};
 use rand::Rng;
 use serde_json;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct RandomBot {
     player_data: PlayerData,
 }
@@ -21,10 +22,16 @@ impl GameObject for RandomBot {
     fn to_json(&self) -> serde_json::Value {
         serde_json::json!({})
     }
-    fn from_json(&mut self, _data: &serde_json::Value) {}
+    fn from_json(&mut self, _data: &serde_json::Value) -> NaughtsResult<()> {
+        Ok(())
+    }
 }
 
 impl GamePlayer for RandomBot {
+    fn clone_box(&self) -> Box<dyn GamePlayer> {
+        Box::new(self.clone())
+    }
+
     fn get_data(&self) -> &PlayerData {
         &self.player_data
     }
@@ -1,23 +1,17 @@
-use crate::engine::errors::StringError;
-use mongodb::{bson, db::ThreadedDatabase, doc, Client, ThreadedClient};
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+use mongodb::{bson, db::ThreadedDatabase, doc, oid, Client, ThreadedClient};
 
 use std::str::FromStr;
 
-impl From<mongodb::error::Error> for StringError {
-    fn from(e: mongodb::error::Error) -> StringError {
-        StringError::new(format!("MongoDB error: {}", e.to_string()).as_str())
+impl From<mongodb::error::Error> for NaughtsError {
+    fn from(e: mongodb::error::Error) -> NaughtsError {
+        NaughtsError::Storage(format!("MongoDB error: {}", e))
     }
 }
 
-impl From<serde_json::Error> for StringError {
-    fn from(e: serde_json::Error) -> StringError {
-        StringError::new(format!("Error parsing JSON: {}", e.to_string()).as_str())
-    }
-}
-
-impl From<bson::oid::Error> for StringError {
-    fn from(e: bson::oid::Error) -> StringError {
-        StringError::new(format!("BSON error: {}", e.to_string()).as_str())
+impl From<oid::Error> for NaughtsError {
+    fn from(e: oid::Error) -> NaughtsError {
+        NaughtsError::Storage(format!("BSON error: {}", e))
     }
 }
 
@@ -25,17 +19,11 @@ pub struct BotDB {
     client: Client,
 }
 
-impl Default for BotDB {
-    fn default() -> Self {
-        BotDB::new()
-    }
-}
-
 impl BotDB {
-    pub fn new() -> Self {
-        BotDB {
-            client: Client::connect("localhost", 27017).expect("Failed to connect to MongoDB"),
-        }
+    pub fn new() -> NaughtsResult<Self> {
+        let client = Client::connect("localhost", 27017)
+            .map_err(|e| NaughtsError::Storage(format!("Failed to connect to MongoDB: {}", e)))?;
+        Ok(BotDB { client })
     }
 
     pub fn save_bot(
@@ -43,41 +31,40 @@ impl BotDB {
         bot_name: &str,
         recipe: &serde_json::Value,
         score: f32,
-    ) -> Result<String, StringError> {
+    ) -> NaughtsResult<String> {
         let coll = self.client.db("naughts").collection("bots");
-        let insert_result = coll
-            .insert_one(
-                doc! { "name": bot_name, "recipe": format!("{}", recipe), "score": score},
-                None,
-            )
-            .unwrap();
+        let insert_result = coll.insert_one(
+            doc! { "name": bot_name, "recipe": format!("{}", recipe), "score": score},
+            None,
+        )?;
 
         match insert_result.inserted_id {
             Some(x) => Ok(x.to_string()),
             None => match insert_result.write_exception {
-                Some(x) => Err(StringError::new(
-                    format!("Error saving bot: {}", x.message).as_str(),
-                )),
-                None => Err(StringError::new("Error saving bot")),
+                Some(x) => Err(NaughtsError::Storage(format!(
+                    "Error saving bot: {}",
+                    x.message
+                ))),
+                None => Err(NaughtsError::Storage(String::from("Error saving bot"))),
             },
         }
     }
 
-    pub fn load_bot(&mut self, id: &str) -> Result<serde_json::Value, StringError> {
+    pub fn load_bot(&mut self, id: &str) -> NaughtsResult<serde_json::Value> {
         let coll = self.client.db("naughts").collection("bots");
         let rec = coll.find_one(
-            Some(doc! {"_id": bson::oid::ObjectId::with_string(id)?}),
+            Some(doc! {"_id": oid::ObjectId::with_string(id)?}),
             None,
         )?;
         let recipe = match rec {
             Some(x) => x
                 .get("recipe")
-                .ok_or_else(|| StringError::new("Bot not found"))?
+                .ok_or_else(|| NaughtsError::Storage(String::from("Bot not found")))?
                 .to_string(),
-            None => return Err(StringError::new("Bot not found")),
+            None => return Err(NaughtsError::Storage(String::from("Bot not found"))),
         };
 
         // recipes are enclosed in double quotes.
         Ok(serde_json::Value::from_str(recipe.trim_matches('"'))?)
     }
-}
\ No newline at end of file
+}
@@ -1,61 +1,80 @@
-use crate::bots::genbot3::gbot::GenBot3;
-use crate::bots::nbot1::nbot::NBot1;
-use crate::bots::omnibot::obot::OmniBot;
-use crate::bots::randombot::rbot::RandomBot;
-
+use crate::engine::botregistry::{find_bot, BotContext, BotSpec};
+use crate::engine::errors::NaughtsResult;
 use crate::engine::gamebase::GameInfo;
 use crate::engine::gameconfig::BotConfig;
+use crate::engine::gamefactory::GameType;
 use crate::engine::gameplayer::GamePlayer;
 
-use crate::games::connect4;
-use crate::games::naughts;
+use std::fmt;
+use std::str::FromStr;
 
+/// A registered bot, plus its argument if it takes one, e.g. "external:<command>".
+///
+/// Bots are added with register_bot().
 #[derive(Debug, Clone)]
-pub enum NaughtsBot {
-    Human,
+pub struct BotType {
+    spec: BotSpec,
+    arg: Option<String>,
 }
 
-#[derive(Debug, Clone)]
-pub enum Connect4Bot {
-    Human,
+impl BotType {
+    pub fn get_spec(&self) -> &BotSpec {
+        &self.spec
+    }
+
+    pub fn get_arg(&self) -> Option<&str> {
+        self.arg.as_deref()
+    }
+
+    /// Check that this bot can play the specified game.
+    pub fn check_game(&self, game: &GameType) -> Result<(), String> {
+        if self.spec.can_play(game) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Bot {} can only play {}, not {}",
+                self,
+                self.spec.games.join(", "),
+                game
+            ))
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
-pub enum BotType {
-    RandomBot,
-    GenBot3,
-    NBot1,
-    OmniBot,
-    Naughts(NaughtsBot),
-    Connect4(Connect4Bot),
+impl FromStr for BotType {
+    type Err = String;
+
+    /// Parse a bot name, e.g. "genbot3", "naughts.human" or "external:<command>".
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, arg) = match value.split_once(':') {
+            Some((name, arg)) => (name, Some(arg.trim())),
+            None => (value, None),
+        };
+        let spec = find_bot(name).ok_or_else(|| {
+            format!(
+                "Unknown bot '{}'. Use the list-bots command to see available bots",
+                value
+            )
+        })?;
+
+        match (&spec.arg, arg) {
+            (Some(_), Some(x)) if !x.is_empty() => Ok(BotType {
+                spec: spec.clone(),
+                arg: Some(x.to_string()),
+            }),
+            (Some(x), _) => Err(format!("Missing {} for {} bot: {}", x, spec.name, value)),
+            (None, Some(_)) => Err(format!("Bot {} does not take an argument", spec.name)),
+            (None, None) => Ok(BotType { spec, arg: None }),
+        }
+    }
 }
 
-impl<S> From<S> for BotType
-where
-    S: Into<String>,
-{
-    fn from(value: S) -> Self {
-        let svalue: String = value.into();
-        match svalue.to_ascii_lowercase().as_str() {
-            "randombot" => BotType::RandomBot,
-            "genbot3" => BotType::GenBot3,
-            "nbot1" => BotType::NBot1,
-            "omnibot" => BotType::OmniBot,
-            x if x.starts_with("naughts") => {
-                println!("Matched human");
-                match svalue.splitn(2, '.').nth(1) {
-                    Some("human") => BotType::Naughts(NaughtsBot::Human),
-                    _ => panic!("Unknown bot: {:?}", svalue.splitn(2, '.').nth(2)),
-                }
-            }
-            x if x.starts_with("connect4") => {
-                println!("matched connect4");
-                match svalue.splitn(2, '.').nth(1) {
-                    Some("human") => BotType::Connect4(Connect4Bot::Human),
-                    _ => panic!("Unknown bot: {}", svalue),
-                }
-            }
-            _ => panic!("Unknown bot: {}", svalue),
+impl fmt::Display for BotType {
+    /// Write the bot name, as accepted by from_str().
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.arg {
+            Some(ref x) => write!(f, "{}:{}", self.spec.name, x),
+            None => write!(f, "{}", self.spec.name),
         }
     }
 }
@@ -99,62 +118,60 @@ impl BotFactory {
     }
 
     pub fn create_bot(&self, bot_type: &BotType) -> DynBot {
-        match bot_type {
-            BotType::RandomBot => Box::new(RandomBot::new(&self.game_info)),
-            BotType::GenBot3 => Box::new(GenBot3::new(&self.game_info)),
-            BotType::NBot1 => Box::new(NBot1::new(&self.game_info)),
-            BotType::OmniBot => Box::new(OmniBot::new(&self.game_info)),
-            BotType::Naughts(NaughtsBot::Human) => {
-                Box::new(naughts::bots::hbot::HumanBot::new(&self.game_info))
-            }
-            BotType::Connect4(Connect4Bot::Human) => {
-                Box::new(connect4::bots::hbot::HumanConnect4Bot::new(&self.game_info))
-            }
-        }
+        bot_type.spec.create(&BotContext {
+            game_info: &self.game_info,
+            bot_config: &self.bot_config,
+            arg: bot_type.get_arg(),
+        })
     }
 
-    fn create_bot_with_recipe(&self, index: usize, bot_type: &BotType) -> DynBot {
-        let mut bot = self.create_bot(bot_type);
-        if bot.is_genetic() && !self.recipes[index].is_null() {
-            bot.from_json(&self.recipes[index]);
-        }
-        bot
+    fn create_bot_with_recipe(&self, index: usize, bot_type: &BotType) -> NaughtsResult<DynBot> {
+        self.create_bot_with_custom_recipe(bot_type, &self.recipes[index])
     }
 
+    /// Create a bot and load the recipe into it, if it is a genetic bot.
     pub fn create_bot_with_custom_recipe(
         &self,
         bot_type: &BotType,
         recipe: &serde_json::Value,
-    ) -> DynBot {
+    ) -> NaughtsResult<DynBot> {
         let mut bot = self.create_bot(bot_type);
         if bot.is_genetic() && !recipe.is_null() {
-            bot.from_json(&recipe);
+            bot.from_json(recipe)?;
         }
-        bot
+        Ok(bot)
     }
 
-    pub fn create_genetic_bot_with_custom_recipe(&self, recipe: &serde_json::Value) -> DynBot {
+    pub fn create_genetic_bot_with_custom_recipe(
+        &self,
+        recipe: &serde_json::Value,
+    ) -> NaughtsResult<DynBot> {
         match self.genetic_index {
             Some(x) => self.create_bot_with_custom_recipe(&self.bot_config.bot_types[x], recipe),
             None => panic!("Can't create genetic bot before setting genetic index!"),
         }
     }
 
-    pub fn create_bots(&self) -> (DynBot, DynBot) {
-        (
-            self.create_bot_with_recipe(0, &self.bot_config.bot_types[0]),
-            self.create_bot_with_recipe(1, &self.bot_config.bot_types[1]),
-        )
+    /// Create the bot at the specified index, with its recipe.
+    pub fn create_bot_at(&self, index: usize) -> NaughtsResult<DynBot> {
+        self.create_bot_with_recipe(index, &self.bot_config.bot_types[index])
+    }
+
+    pub fn create_bots(&self) -> NaughtsResult<(DynBot, DynBot)> {
+        Ok((
+            self.create_bot_with_recipe(0, &self.bot_config.bot_types[0])?,
+            self.create_bot_with_recipe(1, &self.bot_config.bot_types[1])?,
+        ))
     }
 
     pub fn create_bots_with_custom_recipe(
         &self,
         recipes: [&serde_json::Value; 2],
-    ) -> (DynBot, DynBot) {
-        (
-            self.create_bot_with_custom_recipe(&self.bot_config.bot_types[0], recipes[0]),
-            self.create_bot_with_custom_recipe(&self.bot_config.bot_types[1], recipes[1]),
-        )
+    ) -> NaughtsResult<(DynBot, DynBot)> {
+        Ok((
+            self.create_bot_with_custom_recipe(&self.bot_config.bot_types[0], recipes[0])?,
+            self.create_bot_with_custom_recipe(&self.bot_config.bot_types[1], recipes[1])?,
+        ))
     }
 
     pub fn bot_names(&self) -> (&str, &str) {
@@ -0,0 +1,217 @@
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::bots::external::ebot::ExternalBot;
+use crate::bots::genbot3::gbot::{GenBot3, GenBot3Config};
+use crate::bots::nbot1::nbot::{NBot1, NBot1Config};
+use crate::bots::omnibot::obot::OmniBot;
+use crate::bots::randombot::rbot::RandomBot;
+
+use crate::engine::botfactory::DynBot;
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+use crate::engine::gamebase::GameInfo;
+use crate::engine::gameconfig::BotConfig;
+use crate::engine::gamefactory::GameType;
+
+use crate::games::connect4;
+use crate::games::hex;
+use crate::games::naughts;
+
+/// Everything a bot constructor gets to work with.
+pub struct BotContext<'a> {
+    pub game_info: &'a GameInfo,
+    pub bot_config: &'a BotConfig,
+    /// The text after ':' in the bot name, for bots that take an argument.
+    pub arg: Option<&'a str>,
+}
+
+/// Creates a new bot. This is a closure so that bots added as a library can carry their own
+/// options.
+pub type BotConstructor = Arc<dyn Fn(&BotContext) -> DynBot + Send + Sync>;
+
+/// A bot or game option, e.g. from the [genbot3] section of an experiment file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamSpec {
+    pub name: String,
+    pub description: String,
+    pub default: serde_json::Value,
+}
+
+/// A bot that can be chosen by name.
+#[derive(Clone)]
+pub struct BotSpec {
+    pub name: String,
+    pub description: String,
+    /// The games this bot can play. Empty means any game.
+    pub games: Vec<String>,
+    /// What the text after ':' in the bot name means, e.g. "command" for
+    /// "external:<command>". Bots with no arg don't accept one.
+    pub arg: Option<String>,
+    pub params: Vec<ParamSpec>,
+    /// Whether the bot can be chosen in the web UI. Bots that need a console or run other
+    /// programs can't.
+    pub web: bool,
+    constructor: BotConstructor,
+}
+
+impl BotSpec {
+    pub fn new<F>(name: &str, description: &str, constructor: F) -> Self
+    where
+        F: Fn(&BotContext) -> DynBot + Send + Sync + 'static,
+    {
+        BotSpec {
+            name: name.to_ascii_lowercase(),
+            description: description.to_string(),
+            games: Vec::new(),
+            arg: None,
+            params: Vec::new(),
+            web: true,
+            constructor: Arc::new(constructor),
+        }
+    }
+
+    /// Only allow this bot to play the specified games.
+    pub fn with_games(mut self, games: &[&str]) -> Self {
+        self.games = games.iter().map(|x| x.to_string()).collect();
+        self
+    }
+
+    /// Require an argument after ':' in the bot name.
+    pub fn with_arg(mut self, arg: &str) -> Self {
+        self.arg = Some(arg.to_string());
+        self
+    }
+
+    /// Don't offer this bot in the web UI.
+    pub fn without_web(mut self) -> Self {
+        self.web = false;
+        self
+    }
+
+    pub fn with_param(mut self, name: &str, description: &str, default: serde_json::Value) -> Self {
+        self.params.push(ParamSpec {
+            name: name.to_string(),
+            description: description.to_string(),
+            default,
+        });
+        self
+    }
+
+    pub fn can_play(&self, game: &GameType) -> bool {
+        self.games.is_empty() || self.games.contains(&game.to_string())
+    }
+
+    pub fn create(&self, context: &BotContext) -> DynBot {
+        (self.constructor)(context)
+    }
+}
+
+impl fmt::Debug for BotSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BotSpec")
+            .field("name", &self.name)
+            .field("games", &self.games)
+            .field("arg", &self.arg)
+            .field("web", &self.web)
+            .finish()
+    }
+}
+
+fn builtin_bots() -> Vec<BotSpec> {
+    let genbot3 = GenBot3Config::default();
+    let nbot1 = NBot1Config::default();
+    vec![
+        BotSpec::new("randombot", "Plays a random available move", |x| {
+            Box::new(RandomBot::new(x.game_info))
+        }),
+        BotSpec::new(
+            "genbot3",
+            "Genetic bot built from a network of logic gates",
+            |x| Box::new(GenBot3::with_config(x.game_info, &x.bot_config.genbot3)),
+        )
+        .with_param(
+            "num_nodes",
+            "The number of logic nodes, not including input nodes",
+            serde_json::json!(genbot3.num_nodes),
+        )
+        .with_param(
+            "num_output_inputs",
+            "The number of nodes connected to each output node",
+            serde_json::json!(genbot3.num_output_inputs),
+        ),
+        BotSpec::new("nbot1", "Genetic bot built from a neural network", |x| {
+            Box::new(NBot1::with_config(x.game_info, &x.bot_config.nbot1))
+        })
+        .with_param(
+            "mutation_range",
+            "Mutation adds a random value in (-mutation_range, mutation_range)",
+            serde_json::json!(nbot1.mutation_range),
+        ),
+        BotSpec::new(
+            "omnibot",
+            "Magic bot that plays every available move in a magic batch",
+            |x| Box::new(OmniBot::new(x.game_info)),
+        ),
+        BotSpec::new(
+            "external",
+            "Runs <command> and talks to it over stdin/stdout",
+            |x| {
+                Box::new(ExternalBot::new(
+                    x.game_info,
+                    x.arg.unwrap_or_default(),
+                    x.bot_config.move_time_limit,
+                ))
+            },
+        )
+        .with_arg("command")
+        .without_web(),
+        BotSpec::new("naughts.human", "Human player using the console", |x| {
+            Box::new(naughts::bots::hbot::HumanBot::new(x.game_info))
+        })
+        .with_games(&["naughts"])
+        .without_web(),
+        BotSpec::new("connect4.human", "Human player using the console", |x| {
+            Box::new(connect4::bots::hbot::HumanConnect4Bot::new(x.game_info))
+        })
+        .with_games(&["connect4"])
+        .without_web(),
+        BotSpec::new("hex.human", "Human player using the console", |x| {
+            Box::new(hex::bots::hbot::HumanHexBot::new(x.game_info))
+        })
+        .with_games(&["hex"])
+        .without_web(),
+    ]
+}
+
+fn registry() -> &'static RwLock<Vec<BotSpec>> {
+    static REGISTRY: OnceLock<RwLock<Vec<BotSpec>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(builtin_bots()))
+}
+
+/// Add a bot, so that it can be chosen by name like the built-in bots.
+pub fn register_bot(spec: BotSpec) -> NaughtsResult<()> {
+    let mut bots = registry().write().expect("Bot registry lock poisoned");
+    if bots.iter().any(|x| x.name == spec.name) {
+        return Err(NaughtsError::Config(format!(
+            "A bot named '{}' is already registered",
+            spec.name
+        )));
+    }
+    bots.push(spec);
+    Ok(())
+}
+
+/// Find a registered bot by name.
+pub fn find_bot(name: &str) -> Option<BotSpec> {
+    let name = name.to_ascii_lowercase();
+    let bots = registry().read().expect("Bot registry lock poisoned");
+    bots.iter().find(|x| x.name == name).cloned()
+}
+
+/// Get all registered bots, in the order they were registered.
+pub fn get_bots() -> Vec<BotSpec> {
+    registry()
+        .read()
+        .expect("Bot registry lock poisoned")
+        .clone()
+}
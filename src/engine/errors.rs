@@ -1,21 +1,49 @@
+use std::error::Error;
 use std::fmt;
+use std::io;
 
-#[derive(Default, Debug, Clone)]
-pub struct StringError {
-    msg: String,
+/// Errors from the engine, storage and runners.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NaughtsError {
+    /// Invalid CLI args or experiment config.
+    Config(String),
+    /// Error reading or writing a file or stream.
+    Io(String),
+    /// A bot recipe that couldn't be parsed.
+    RecipeParse(String),
+    /// Error saving or loading bots in BotDB.
+    Storage(String),
+    /// A move or request that the rules of the game don't allow.
+    GameRule(String),
+    /// Saved game state that is malformed or inconsistent.
+    InvalidState(String),
 }
 
-impl StringError {
-    pub fn new(err: &str) -> Self {
-        StringError {
-            msg: err.to_owned(),
+pub type NaughtsResult<T> = Result<T, NaughtsError>;
+
+impl fmt::Display for NaughtsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NaughtsError::Config(x) => write!(f, "{}", x),
+            NaughtsError::Io(x) => write!(f, "IO error: {}", x),
+            NaughtsError::RecipeParse(x) => write!(f, "Invalid recipe: {}", x),
+            NaughtsError::Storage(x) => write!(f, "Storage error: {}", x),
+            NaughtsError::GameRule(x) => write!(f, "{}", x),
+            NaughtsError::InvalidState(x) => write!(f, "{}", x),
         }
     }
+}
 
+impl Error for NaughtsError {}
+
+impl From<io::Error> for NaughtsError {
+    fn from(e: io::Error) -> NaughtsError {
+        NaughtsError::Io(e.to_string())
+    }
 }
 
-impl fmt::Display for StringError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.msg)
+impl From<serde_json::Error> for NaughtsError {
+    fn from(e: serde_json::Error) -> NaughtsError {
+        NaughtsError::RecipeParse(format!("Error parsing JSON: {}", e))
     }
 }
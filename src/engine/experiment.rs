@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::bots::genbot3::gbot::GenBot3Config;
+use crate::bots::nbot1::nbot::NBot1Config;
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+use crate::engine::gameregistry::GameOptions;
+
+/// Batch options in an experiment file. These match the CLI options of the same name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BatchSection {
+    pub size: Option<u32>,
+    pub magic: Option<bool>,
+    pub memo: Option<bool>,
+    pub magic_depth: Option<u32>,
+    pub magic_samples: Option<u32>,
+    pub illegal: Option<String>,
+    pub illegal_penalty: Option<f32>,
+    pub move_time: Option<u64>,
+    pub game_time: Option<u64>,
+    pub timeout: Option<String>,
+}
+
+/// Genetic options in an experiment file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneticSection {
+    pub generations: Option<u32>,
+    pub samples: Option<u32>,
+    pub keep: Option<u32>,
+    pub wild: Option<u32>,
+    /// Number of worker threads running batches.
+    pub threads: Option<u32>,
+    /// How far the score threshold moves towards a new best score each generation (0-1).
+    pub lifting_factor: Option<f32>,
+    pub status_port: Option<u16>,
+}
+
+/// Nim options in an experiment file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NimSection {
+    pub heaps: Option<Vec<u32>>,
+    pub misere: Option<bool>,
+    pub subtract: Option<Vec<u32>>,
+}
+
+/// Everything needed to repeat a run, loaded from a TOML or JSON file with --config.
+///
+/// Every value is optional. Values given on the command line override values in the file.
+/// For example:
+///
+/// ```toml
+/// game = "connect4"
+/// bots = ["genbot3", "omnibot"]
+/// output_dir = "results/connect4"
+///
+/// [batch]
+/// size = 100
+///
+/// [genetic]
+/// generations = 1000
+/// samples = 10
+/// threads = 8
+///
+/// [genbot3]
+/// num_nodes = 200
+/// num_output_inputs = 30
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExperimentConfig {
+    pub game: Option<String>,
+    /// Options for the game, e.g. size = 9 for hex. Use list-games to see them.
+    pub game_options: Option<GameOptions>,
+    /// The bots to play. Only the tournament command accepts more than 2.
+    pub bots: Option<Vec<String>>,
+    /// BotDB id to load the recipe from.
+    pub botid: Option<String>,
+    /// File to load the recipe from.
+    pub recipe: Option<String>,
+    /// Where the evolve command writes scores.csv and the effective config.
+    /// Defaults to the exe dir.
+    pub output_dir: Option<String>,
+    pub batch: BatchSection,
+    pub genetic: GeneticSection,
+    pub nim: NimSection,
+    pub genbot3: Option<GenBot3Config>,
+    pub nbot1: Option<NBot1Config>,
+}
+
+impl ExperimentConfig {
+    /// Load an experiment file. Files ending in ".toml" are parsed as TOML, anything else as JSON.
+    pub fn load(path: &Path) -> NaughtsResult<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            NaughtsError::Io(format!(
+                "Error reading config file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let is_toml = path.extension().is_some_and(|x| x == "toml");
+        if is_toml {
+            toml::from_str(&content).map_err(|e| {
+                NaughtsError::Config(format!(
+                    "Invalid TOML in config file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+        } else {
+            serde_json::from_str(&content).map_err(|e| {
+                NaughtsError::Config(format!(
+                    "Invalid JSON in config file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+        }
+    }
+
+    /// Save as JSON, which can be loaded again with --config.
+    pub fn save(&self, path: &Path) -> NaughtsResult<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| NaughtsError::Config(format!("Error serialising config: {}", e)))?;
+        fs::write(path, content).map_err(|e| {
+            NaughtsError::Io(format!(
+                "Error writing config file {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+/// Find the value of --config in the CLI args, so that the file can be loaded before the
+/// other args are parsed.
+pub fn find_config_arg(args: &[String]) -> Option<String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--config" {
+            return iter.next().cloned();
+        }
+        if let Some(x) = arg.strip_prefix("--config=") {
+            return Some(x.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_and_json_match() {
+        let toml_config: ExperimentConfig = toml::from_str(
+            r#"
+            game = "nim"
+            bots = ["nbot1", "randombot"]
+
+            [genetic]
+            threads = 2
+            lifting_factor = 0.5
+
+            [nim]
+            heaps = [1, 2, 3]
+
+            [nbot1]
+            mutation_range = 1.5
+            "#,
+        )
+        .unwrap();
+        let json_config: ExperimentConfig = serde_json::from_str(
+            r#"{
+                "game": "nim",
+                "bots": ["nbot1", "randombot"],
+                "genetic": {"threads": 2, "lifting_factor": 0.5},
+                "nim": {"heaps": [1, 2, 3]},
+                "nbot1": {"mutation_range": 1.5}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(toml_config, json_config);
+        assert_eq!(toml_config.genetic.threads, Some(2));
+        assert_eq!(toml_config.batch.size, None);
+
+        let roundtrip: ExperimentConfig =
+            serde_json::from_str(&serde_json::to_string(&json_config).unwrap()).unwrap();
+        assert_eq!(roundtrip, json_config);
+    }
+
+    #[test]
+    fn test_find_config_arg() {
+        let args: Vec<String> = vec!["naughts batch", "--size", "10", "--config", "x.toml"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(find_config_arg(&args), Some(String::from("x.toml")));
+        assert_eq!(
+            find_config_arg(&[String::from("--config=y.json")]),
+            Some(String::from("y.json"))
+        );
+        assert_eq!(find_config_arg(&[]), None);
+    }
+}
@@ -1,21 +1,47 @@
-use crate::engine::botfactory::BotFactory;
-use crate::engine::gameconfig::BatchConfig;
+use crate::engine::botfactory::{BotFactory, DynBot};
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+use crate::engine::gameconfig::{BatchConfig, IllegalMovePolicy, TimeoutAction};
 use crate::engine::gamefactory::GameFactory;
-use crate::engine::gameobject::GameObject;
-use crate::engine::gameresult::GameResult;
+use crate::engine::gameobject::{invalid_state, GameObject};
+use crate::engine::gameresult::{GameResult, ThinkTime};
 use log::*;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct GameInfo {
     pub input_count: u32,
     pub output_count: u32,
 }
 
 pub trait GameTrait: GameObject {
+    /// Create a boxed copy of this game, including all current state.
+    /// This is much cheaper than a to_json()/from_json() round trip.
+    fn clone_box(&self) -> Box<dyn GameTrait>;
+
     fn get_identities(&self) -> [char; 2];
     fn get_game_info(&self) -> GameInfo;
     fn get_inputs(&self, index: usize) -> (Vec<f32>, Vec<u32>);
     fn update(&mut self, index: usize, output: u32);
+
+    /// Get the index of the player to move after player `index` has moved.
+    /// Games that allow extra turns should override this.
+    fn get_next_index(&self, index: usize) -> usize {
+        if index == 0 {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Get the index of the player to move, for games that record it in their state.
+    /// Other games return None, and the caller keeps track of whose turn it is.
+    fn get_player_to_move(&self) -> Option<usize> {
+        None
+    }
+
     fn is_ended(&self) -> bool;
     fn get_result(&self) -> GameResult;
     fn show(&self, _indent: u8) {}
@@ -24,52 +50,217 @@ pub trait GameTrait: GameObject {
         assert!(index < 2, "Index out of bounds on get_identity()!");
         self.get_identities()[index]
     }
+
+    /// Get a key that uniquely identifies the current game state, including anything that
+    /// affects the result, such as turn counts.
+    fn get_state_key(&self) -> String {
+        self.to_json().to_string()
+    }
+}
+
+/// Create a game from saved state. The state must be for the same size of game as the factory
+/// creates, so that bots created for that game can play it.
+pub fn load_game(
+    game_factory: &GameFactory,
+    data: &serde_json::Value,
+) -> NaughtsResult<Box<dyn GameTrait>> {
+    let mut game = game_factory();
+    let game_info = game.get_game_info();
+    game.from_json(data)?;
+    let loaded = game.get_game_info();
+    if loaded != game_info {
+        return Err(invalid_state(
+            "game",
+            format!(
+                "it has {} inputs and {} outputs, expected {} and {}",
+                loaded.input_count,
+                loaded.output_count,
+                game_info.input_count,
+                game_info.output_count
+            ),
+        ));
+    }
+    Ok(game)
+}
+
+/// Get the available move that is numerically closest to the specified output.
+pub fn get_nearest_move(moves: &[u32], output: u32) -> u32 {
+    let mut target = moves[0];
+    let mut lowest_diff: Option<u32> = None;
+    for m in moves.iter() {
+        let diff = (i64::from(output) - i64::from(*m)).unsigned_abs() as u32;
+        if lowest_diff.is_none_or(|x| diff < x) {
+            lowest_diff = Some(diff);
+            target = *m;
+        }
+    }
+    target
+}
+
+/// Return true if the bot's total time for the game is over the game time limit.
+fn is_over_game_time(batch_config: &BatchConfig, game_time: Duration) -> bool {
+    batch_config.game_time_limit > 0
+        && game_time > Duration::from_millis(batch_config.game_time_limit)
+}
+
+/// Return true if a move took longer than the move time limit, or took the bot's total time for
+/// the game over the game time limit.
+fn is_timed_out(batch_config: &BatchConfig, move_time: Duration, game_time: Duration) -> bool {
+    (batch_config.move_time_limit > 0
+        && move_time > Duration::from_millis(batch_config.move_time_limit))
+        || is_over_game_time(batch_config, game_time)
+}
+
+/// Ask the bot for a move, enforcing the time limits and the illegal move policy.
+/// Returns None if the bot forfeits the game.
+///
+/// A bot can't be interrupted in the middle of process(), so the time limits are checked once
+/// it returns. Time spent on retries counts towards the same move.
+///
+/// Once a bot has used up its game time, it isn't asked for any more moves. The timeout was
+/// counted on the move that used it up, and its remaining moves are random.
+fn get_bot_move(
+    bot: &mut DynBot,
+    batch_config: &BatchConfig,
+    inputs: Vec<f32>,
+    available_moves: &[u32],
+    illegal_moves: &mut u32,
+    think_time: &mut ThinkTime,
+) -> Option<u32> {
+    if is_over_game_time(batch_config, think_time.total) {
+        return available_moves.choose(&mut rand::thread_rng()).cloned();
+    }
+
+    let mut retries = 0;
+    let mut elapsed = Duration::default();
+    let output = loop {
+        let start = Instant::now();
+        let output = bot.process(inputs.clone(), available_moves);
+        elapsed += start.elapsed();
+
+        if bot.has_failed() {
+            break None;
+        }
+
+        if bot.has_timed_out() || is_timed_out(batch_config, elapsed, think_time.total + elapsed) {
+            think_time.timeouts += 1;
+            debug!("{} exceeded the time limit ({:?})", bot.label(), elapsed);
+            break match batch_config.timeout_action {
+                TimeoutAction::RandomMove => {
+                    available_moves.choose(&mut rand::thread_rng()).cloned()
+                }
+                TimeoutAction::Forfeit => None,
+            };
+        }
+
+        if available_moves.contains(&output) {
+            break Some(output);
+        }
+
+        *illegal_moves += 1;
+        debug!(
+            "{} made illegal move {}, expected one of {:?}",
+            bot.label(),
+            output,
+            available_moves
+        );
+        match batch_config.illegal_move_policy {
+            IllegalMovePolicy::Snap => break Some(get_nearest_move(available_moves, output)),
+            IllegalMovePolicy::Forfeit => break None,
+            IllegalMovePolicy::Retry(max_retries) => {
+                if retries >= max_retries {
+                    break None;
+                }
+                retries += 1;
+            }
+        }
+    };
+
+    think_time.add_move(elapsed);
+    output
+}
+
+/// Record illegal moves in the result, and apply the penalty to each bot's score.
+/// The winner is still decided by the game.
+fn apply_illegal_moves(
+    result: &mut GameResult,
+    batch_config: &BatchConfig,
+    illegal_moves: [u32; 2],
+) {
+    result.set_illegal_moves(illegal_moves);
+    let penalty = batch_config.illegal_move_penalty;
+    result.set_penalties([
+        penalty * illegal_moves[0] as f32,
+        penalty * illegal_moves[1] as f32,
+    ]);
 }
 
 pub fn run_one_game(
+    batch_config: &BatchConfig,
     log_output: bool,
-    game_factory: GameFactory,
+    game_factory: &GameFactory,
     bot_factory: &BotFactory,
-) -> GameResult {
+) -> NaughtsResult<GameResult> {
     let mut game = game_factory();
     let identities = game.get_identities();
-    let (mut bot1, mut bot2) = bot_factory.create_bots();
+    let (bot1, bot2) = bot_factory.create_bots()?;
+    let mut bots = [bot1, bot2];
 
-    bot1.setup(identities[0], identities[1]);
-    bot2.setup(identities[1], identities[0]);
+    bots[0].setup(identities[0], identities[1]);
+    bots[1].setup(identities[1], identities[0]);
 
+    let mut illegal_moves = [0, 0];
+    let mut think_times = [ThinkTime::default(); 2];
+    let mut forfeit_index = None;
     let mut bot_index = 0;
     while !game.is_ended() {
         let (inputs, available_moves) = game.get_inputs(bot_index);
 
-        let output = match bot_index {
-            0 => bot1.process(inputs, &available_moves),
-            1 => bot2.process(inputs, &available_moves),
-            _ => panic!("Invalid bot index: {}", bot_index),
-        };
-        game.update(bot_index, output);
+        let output = get_bot_move(
+            &mut bots[bot_index],
+            batch_config,
+            inputs,
+            &available_moves,
+            &mut illegal_moves[bot_index],
+            &mut think_times[bot_index],
+        );
+        match output {
+            Some(x) => game.update(bot_index, x),
+            None => {
+                forfeit_index = Some(bot_index);
+                break;
+            }
+        }
         if log_output {
             game.show(4);
         }
 
-        bot_index = if bot_index == 0 { 1 } else { 0 };
+        bot_index = game.get_next_index(bot_index);
     }
 
-    if bot1.should_show_result() || bot2.should_show_result() {
+    if bots[0].should_show_result() || bots[1].should_show_result() {
         game.show(4);
     }
 
-    game.get_result()
+    let mut result = match forfeit_index {
+        Some(x) => GameResult::new_forfeit(identities, x),
+        None => game.get_result(),
+    };
+    apply_illegal_moves(&mut result, batch_config, illegal_moves);
+    result.set_think_times(think_times);
+    bots[0].game_over(&result);
+    bots[1].game_over(&result);
+    Ok(result)
 }
 
 pub fn run_batch(
     batch_config: &BatchConfig,
     log_output: bool,
-    game_factory: GameFactory,
+    game_factory: &GameFactory,
     bot_factory: &BotFactory,
-) -> GameResult {
+) -> NaughtsResult<GameResult> {
     if batch_config.magic {
-        return run_magic_batch(log_output, game_factory, bot_factory);
+        return run_magic_batch(batch_config, log_output, game_factory, bot_factory);
     }
 
     let game = &game_factory();
@@ -80,10 +271,16 @@ pub fn run_batch(
     let mut total_score1 = 0.0;
     let mut total_score2 = 0.0;
     let mut num_draws: u32 = 0;
+    let mut illegal_moves = [0, 0];
+    let mut think_times = [ThinkTime::default(); 2];
     for _ in 0..batch_config.batch_size {
-        let result = run_one_game(false, game_factory, bot_factory);
+        let result = run_one_game(batch_config, false, game_factory, bot_factory)?;
         total_score1 += result.get_score1();
         total_score2 += result.get_score2();
+        illegal_moves[0] += result.get_illegal_moves()[0];
+        illegal_moves[1] += result.get_illegal_moves()[1];
+        think_times[0].add(&result.get_think_times()[0]);
+        think_times[1].add(&result.get_think_times()[1]);
 
         match result.get_winner() {
             Some(0) => wins1 += 1,
@@ -97,155 +294,662 @@ pub fn run_batch(
     final_result.set_batch();
     final_result.set_score1(total_score1 / batch_config.batch_size as f32);
     final_result.set_score2(total_score2 / batch_config.batch_size as f32);
+    final_result.set_illegal_moves(illegal_moves);
+    final_result.set_think_times(think_times);
 
     if log_output {
         let (bot1_name, bot2_name) = bot_factory.bot_names();
         info!("{} WINS: {}", bot1_name, wins1);
         info!("{} WINS: {}", bot2_name, wins2);
-        info!("DRAW/TIE: {}\n", num_draws);
+        info!("DRAW/TIE: {}", num_draws);
+        info!("{} ILLEGAL MOVES: {}", bot1_name, illegal_moves[0]);
+        info!("{} ILLEGAL MOVES: {}", bot2_name, illegal_moves[1]);
+        info!("{} THINK TIME: {}", bot1_name, think_times[0]);
+        info!("{} THINK TIME: {}\n", bot2_name, think_times[1]);
 
         info!("Average Scores:");
         info!("{}: {:.3}", bot1_name, final_result.get_score1());
         info!("{}: {:.3}", bot2_name, final_result.get_score2());
     }
 
-    final_result
+    Ok(final_result)
+}
+
+/// Totals for all games played out from one position in a magic batch.
+#[derive(Debug, Clone, Default)]
+struct MagicTotals {
+    count: u32,
+    wins: [u32; 2],
+    draws: u32,
+    scores: [f32; 2],
+    illegal_moves: [u32; 2],
+    think_times: [ThinkTime; 2],
+}
+
+impl MagicTotals {
+    /// Add the result of one completed game.
+    fn add_result(
+        &mut self,
+        mut result: GameResult,
+        batch_config: &BatchConfig,
+        illegal_moves: [u32; 2],
+        think_times: [ThinkTime; 2],
+    ) {
+        apply_illegal_moves(&mut result, batch_config, illegal_moves);
+        self.think_times[0].add(&think_times[0]);
+        self.think_times[1].add(&think_times[1]);
+        self.count += 1;
+        self.scores[0] += result.get_score1();
+        self.scores[1] += result.get_score2();
+        self.illegal_moves[0] += illegal_moves[0];
+        self.illegal_moves[1] += illegal_moves[1];
+
+        match result.get_winner() {
+            Some(0) => self.wins[0] += 1,
+            Some(1) => self.wins[1] += 1,
+            Some(_) => panic!("Unknown winner index: {:?}", result.get_winner()),
+            None => self.draws += 1,
+        }
+    }
+
+    /// Add the totals from another position.
+    fn add(&mut self, other: &MagicTotals) {
+        self.count += other.count;
+        self.draws += other.draws;
+        for i in 0..2 {
+            self.wins[i] += other.wins[i];
+            self.scores[i] += other.scores[i];
+            self.illegal_moves[i] += other.illegal_moves[i];
+            self.think_times[i].add(&other.think_times[i]);
+        }
+    }
+
+    /// Charge illegal moves made before reaching this position to every game played from it.
+    fn add_illegal_moves(&mut self, batch_config: &BatchConfig, illegal_moves: [u32; 2]) {
+        for (i, num_illegal) in illegal_moves.iter().enumerate() {
+            self.illegal_moves[i] += num_illegal * self.count;
+            self.scores[i] -= batch_config.illegal_move_penalty * (num_illegal * self.count) as f32;
+        }
+    }
+}
+
+/// Shape of the game tree visited by a magic batch.
+#[derive(Debug, Default)]
+struct TreeStats {
+    /// Number of positions visited at each depth, including finished games.
+    positions: Vec<u64>,
+    /// Number of unfinished positions at each depth.
+    expanded: Vec<u64>,
+    /// Number of moves followed from the unfinished positions at each depth.
+    children: Vec<u64>,
 }
 
+impl TreeStats {
+    fn add_position(&mut self, depth: usize, num_children: Option<usize>) {
+        if self.positions.len() <= depth {
+            self.positions.resize(depth + 1, 0);
+            self.expanded.resize(depth + 1, 0);
+            self.children.resize(depth + 1, 0);
+        }
+
+        self.positions[depth] += 1;
+        if let Some(x) = num_children {
+            self.expanded[depth] += 1;
+            self.children[depth] += x as u64;
+        }
+    }
+
+    fn show(&self, identities: [char; 2], totals: &MagicTotals) {
+        info!("GAME TREE:");
+        info!("Total positions: {}", self.positions.iter().sum::<u64>());
+        info!("Terminal positions: {}", totals.count);
+        info!("{} wins: {}", identities[0], totals.wins[0]);
+        info!("{} wins: {}", identities[1], totals.wins[1]);
+        info!("Draws: {}\n", totals.draws);
+
+        info!("Depth  Positions  Branching");
+        for depth in 0..self.positions.len() {
+            let branching = if self.expanded[depth] > 0 {
+                self.children[depth] as f64 / self.expanded[depth] as f64
+            } else {
+                0.0
+            };
+            info!(
+                "{:5}  {:9}  {:9.3}",
+                depth, self.positions[depth], branching
+            );
+        }
+        info!("");
+    }
+}
+
+/// A node in the magic batch game tree.
 struct GameState {
-    game_state: serde_json::Value,
-    bot_state: serde_json::Value,
+    game: Box<dyn GameTrait>,
+    /// Snapshot of the non-magic bot, taken when this node was created.
+    bot: DynBot,
     bot_index: usize,
+    depth: usize,
+    illegal_moves: [u32; 2],
+    think_times: [ThinkTime; 2],
 }
 
 impl GameState {
     pub fn new(
-        game_state: serde_json::Value,
-        bot_state: serde_json::Value,
+        game: Box<dyn GameTrait>,
+        bot: DynBot,
         bot_index: usize,
+        depth: usize,
+        illegal_moves: [u32; 2],
+        think_times: [ThinkTime; 2],
     ) -> Self {
         GameState {
-            game_state,
-            bot_state,
+            game,
+            bot,
             bot_index,
+            depth,
+            illegal_moves,
+            think_times,
         }
     }
 }
 
 pub fn run_magic_batch(
+    batch_config: &BatchConfig,
     log_output: bool,
-    game_factory: GameFactory,
+    game_factory: &GameFactory,
     bot_factory: &BotFactory,
-) -> GameResult {
-    let game = &mut game_factory();
-    let game_info = game.get_game_info();
+) -> NaughtsResult<GameResult> {
+    let game = game_factory();
     let identities = game.get_identities();
-    let (mut bot1, mut bot2) = bot_factory.create_bots();
+    let (mut bot1, mut bot2) = bot_factory.create_bots()?;
 
-    // Ensure 1 and only 1 bot is magic.
+    // If both bots are magic, every move is followed and the whole game tree is visited.
+    let both_magic = bot1.is_magic() && bot2.is_magic();
     let magic_index = if bot1.is_magic() {
-        assert!(!bot1.is_magic(), "Both bots cannot be magic!");
         0
-    } else {
-        assert!(bot2.is_magic(), "At least 1 bot must be magic!");
+    } else if bot2.is_magic() {
         1
+    } else {
+        return Err(NaughtsError::Config(String::from(
+            "A magic batch needs at least 1 magic bot, e.g. omnibot",
+        )));
     };
 
     bot1.setup(identities[1], identities[0]);
     bot2.setup(identities[0], identities[1]);
+    let mut bots = [bot1, bot2];
+
+    let use_memo = if batch_config.magic_memo && both_magic {
+        warn!("Both bots are magic. Ignoring --memo and visiting the full game tree");
+        false
+    } else if batch_config.magic_memo && !bots[1 - magic_index].is_deterministic() {
+        warn!(
+            "{} is not deterministic. Ignoring --memo and playing every game",
+            bots[1 - magic_index].get_name()
+        );
+        false
+    } else if batch_config.magic_memo
+        && (batch_config.move_time_limit > 0 || batch_config.game_time_limit > 0)
+    {
+        warn!("Time limits are set. Ignoring --memo and playing every game");
+        false
+    } else {
+        batch_config.magic_memo
+    };
 
-    // Each game state is a tuple containing game state and bot state.
-    let magic_json = match magic_index {
-        0 => bot1.to_json(),
-        1 => bot2.to_json(),
-        _ => panic!("Invalid bot index: {}", magic_index),
+    let totals = if use_memo {
+        let mut memo = HashMap::new();
+        let totals = run_magic_memo(game, 0, &mut bots, magic_index, batch_config, &mut memo);
+        if log_output {
+            info!("Memoized {} positions", memo.len());
+        }
+        totals
+    } else {
+        let mut stats = TreeStats::default();
+        let totals = run_magic_all(game, &mut bots, magic_index, batch_config, &mut stats);
+        if log_output && both_magic {
+            stats.show(identities, &totals);
+        }
+        totals
     };
-    let initial_state = GameState::new(game.to_json(), magic_json, 0);
+
+    let mut final_result = GameResult::new(identities);
+    final_result.set_batch();
+    final_result.set_score1(totals.scores[0] / totals.count as f32);
+    final_result.set_score2(totals.scores[1] / totals.count as f32);
+    final_result.set_illegal_moves(totals.illegal_moves);
+    final_result.set_think_times(totals.think_times);
+
+    if log_output {
+        let (bot1_name, bot2_name) = bot_factory.bot_names();
+        info!("{} WINS: {}", bot1_name, totals.wins[0]);
+        info!("{} WINS: {}", bot2_name, totals.wins[1]);
+        info!("DRAW/TIE: {}", totals.draws);
+        info!("{} ILLEGAL MOVES: {}", bot1_name, totals.illegal_moves[0]);
+        info!("{} ILLEGAL MOVES: {}", bot2_name, totals.illegal_moves[1]);
+        info!("{} THINK TIME: {}", bot1_name, totals.think_times[0]);
+        info!("{} THINK TIME: {}\n", bot2_name, totals.think_times[1]);
+
+        info!("Average Scores:");
+        info!("{}: {:.3}", bot1_name, final_result.get_score1());
+        info!("{}: {:.3}", bot2_name, final_result.get_score2());
+    }
+
+    Ok(final_result)
+}
+
+/// Play every possible game path against the magic bot(s).
+/// The tree can be limited with the magic depth and magic samples options.
+fn run_magic_all(
+    game: Box<dyn GameTrait>,
+    bots: &mut [DynBot; 2],
+    magic_index: usize,
+    batch_config: &BatchConfig,
+    stats: &mut TreeStats,
+) -> MagicTotals {
+    let game_info = game.get_game_info();
+    let identities = game.get_identities();
+    let other_index = 1 - magic_index;
+    let max_depth = batch_config.magic_depth as usize;
+    let num_samples = batch_config.magic_samples as usize;
+    let mut rng = rand::thread_rng();
+
+    // Each game state holds its own copy of the game and of the non-magic bot, so that every
+    // branch continues from exactly the state it was created in.
+    let initial_state = GameState::new(
+        game,
+        bots[other_index].clone_box(),
+        0,
+        0,
+        [0, 0],
+        [ThinkTime::default(); 2],
+    );
     let mut game_stack = Vec::with_capacity(game_info.output_count as usize * 2);
     game_stack.push(initial_state);
 
-    let mut wins1 = 0;
-    let mut wins2 = 0;
-    let mut num_draws: u32 = 0;
-    let mut total_score1 = 0.0;
-    let mut total_score2 = 0.0;
-
-    let mut count = 0;
-    while !game_stack.is_empty() {
-        let state = game_stack.pop().expect("No game state on stack!");
-        game.from_json(&state.game_state);
-        // TODO: do we need this? bot state generally doesn't change outside mutate().
-        match magic_index {
-            0 => bot2.from_json(&state.bot_state),
-            1 => bot1.from_json(&state.bot_state),
-            _ => panic!("Invalid bot index: {}", magic_index),
-        }
-
-        if game.is_ended() {
-            count += 1;
-
-            let result = game.get_result();
-            total_score1 += result.get_score1();
-            total_score2 += result.get_score2();
-
-            match result.get_winner() {
-                Some(0) => wins1 += 1,
-                Some(1) => wins2 += 1,
-                Some(_) => panic!("Unknown winner index: {:?}", result.get_winner()),
-                None => num_draws += 1,
-            }
-        } else {
-            let (inputs, available_moves) = game.get_inputs(state.bot_index);
+    let mut totals = MagicTotals::default();
+    while let Some(state) = game_stack.pop() {
+        let GameState {
+            mut game,
+            bot,
+            bot_index,
+            depth,
+            mut illegal_moves,
+            mut think_times,
+        } = state;
+        bots[other_index] = bot;
+
+        let mut forfeit_index = None;
+        if !game.is_ended() && max_depth > 0 && depth >= max_depth {
+            // Score the unfinished position by playing it out.
+            forfeit_index = play_out(
+                game.as_mut(),
+                bots,
+                bot_index,
+                batch_config,
+                &mut illegal_moves,
+                &mut think_times,
+            );
+        } else if !game.is_ended() {
+            let (inputs, available_moves) = game.get_inputs(bot_index);
             assert_eq!(
                 inputs.len(),
                 game_info.input_count as usize,
                 "Game returned the wrong number of inputs!"
             );
 
-            let (outputs, magic_json) = match state.bot_index {
-                0 => {
-                    if state.bot_index == magic_index {
-                        (bot1.process_magic(inputs, &available_moves), bot1.to_json())
-                    } else {
-                        (vec![bot1.process(inputs, &available_moves)], bot2.to_json())
-                    }
+            if bots[bot_index].is_magic() {
+                let mut outputs = bots[bot_index].process_magic(inputs, &available_moves);
+                if num_samples > 0 && outputs.len() > num_samples {
+                    outputs = outputs
+                        .choose_multiple(&mut rng, num_samples)
+                        .cloned()
+                        .collect();
                 }
-                1 => {
-                    if state.bot_index == magic_index {
-                        (bot2.process_magic(inputs, &available_moves), bot2.to_json())
-                    } else {
-                        (vec![bot2.process(inputs, &available_moves)], bot1.to_json())
-                    }
+                stats.add_position(depth, Some(outputs.len()));
+                for output in outputs {
+                    let mut new_game = game.clone_box();
+                    new_game.update(bot_index, output);
+                    let new_bot_index = new_game.get_next_index(bot_index);
+                    game_stack.push(GameState::new(
+                        new_game,
+                        bots[other_index].clone_box(),
+                        new_bot_index,
+                        depth + 1,
+                        illegal_moves,
+                        think_times,
+                    ));
                 }
-                _ => panic!("Invalid bot index: {}", state.bot_index),
-            };
-            let new_bot_index = if state.bot_index == 0 { 1 } else { 0 };
+                continue;
+            }
+
+            let output = get_bot_move(
+                &mut bots[bot_index],
+                batch_config,
+                inputs,
+                &available_moves,
+                &mut illegal_moves[bot_index],
+                &mut think_times[bot_index],
+            );
+            match output {
+                Some(x) => {
+                    stats.add_position(depth, Some(1));
+                    game.update(bot_index, x);
+                    let new_bot_index = game.get_next_index(bot_index);
+                    game_stack.push(GameState::new(
+                        game,
+                        bots[other_index].clone_box(),
+                        new_bot_index,
+                        depth + 1,
+                        illegal_moves,
+                        think_times,
+                    ));
+                    continue;
+                }
+                None => forfeit_index = Some(bot_index),
+            }
+        }
+
+        stats.add_position(depth, None);
+        let result = match forfeit_index {
+            Some(x) => GameResult::new_forfeit(identities, x),
+            None => game.get_result(),
+        };
+        totals.add_result(result, batch_config, illegal_moves, think_times);
+    }
+
+    totals
+}
+
+/// Finish a game from the current position, with any magic bots playing random moves.
+/// Returns the index of the bot that forfeited, if any.
+fn play_out(
+    game: &mut dyn GameTrait,
+    bots: &mut [DynBot; 2],
+    mut bot_index: usize,
+    batch_config: &BatchConfig,
+    illegal_moves: &mut [u32; 2],
+    think_times: &mut [ThinkTime; 2],
+) -> Option<usize> {
+    let mut rng = rand::thread_rng();
+    while !game.is_ended() {
+        let (inputs, available_moves) = game.get_inputs(bot_index);
+        let output = if bots[bot_index].is_magic() {
+            available_moves.choose(&mut rng).cloned()
+        } else {
+            get_bot_move(
+                &mut bots[bot_index],
+                batch_config,
+                inputs,
+                &available_moves,
+                &mut illegal_moves[bot_index],
+                &mut think_times[bot_index],
+            )
+        };
+        match output {
+            Some(x) => game.update(bot_index, x),
+            None => return Some(bot_index),
+        }
+        bot_index = game.get_next_index(bot_index);
+    }
+    None
+}
 
+/// Play every possible game against the magic bot, reusing the totals for positions that are
+/// reached by more than one move order.
+///
+/// This only gives the same totals as run_magic_all() if the non-magic bot is deterministic,
+/// because its move from each position is only calculated once. For the same reason, the game
+/// time limit only applies to one move at a time.
+fn run_magic_memo(
+    game: Box<dyn GameTrait>,
+    bot_index: usize,
+    bots: &mut [DynBot; 2],
+    magic_index: usize,
+    batch_config: &BatchConfig,
+    memo: &mut HashMap<(usize, String), MagicTotals>,
+) -> MagicTotals {
+    let key = (bot_index, game.get_state_key());
+    if let Some(x) = memo.get(&key) {
+        return x.clone();
+    }
+
+    let mut totals = MagicTotals::default();
+    if game.is_ended() {
+        let think_times = [ThinkTime::default(); 2];
+        totals.add_result(game.get_result(), batch_config, [0, 0], think_times);
+    } else {
+        let (inputs, available_moves) = game.get_inputs(bot_index);
+        if bot_index == magic_index {
+            let outputs = bots[bot_index].process_magic(inputs, &available_moves);
             for output in outputs {
-                game.update(state.bot_index, output);
-                // Append new game state to the stack.
-                let new_state = GameState::new(game.to_json(), magic_json.clone(), new_bot_index);
-                game_stack.push(new_state);
-                game.from_json(&state.game_state);
+                let mut new_game = game.clone_box();
+                new_game.update(bot_index, output);
+                let new_bot_index = new_game.get_next_index(bot_index);
+                let child = run_magic_memo(
+                    new_game,
+                    new_bot_index,
+                    bots,
+                    magic_index,
+                    batch_config,
+                    memo,
+                );
+                totals.add(&child);
+            }
+        } else {
+            let mut illegal_moves = [0, 0];
+            let mut think_times = [ThinkTime::default(); 2];
+            let output = get_bot_move(
+                &mut bots[bot_index],
+                batch_config,
+                inputs,
+                &available_moves,
+                &mut illegal_moves[bot_index],
+                &mut think_times[bot_index],
+            );
+            match output {
+                Some(x) => {
+                    let mut new_game = game.clone_box();
+                    new_game.update(bot_index, x);
+                    let new_bot_index = new_game.get_next_index(bot_index);
+                    let mut child = run_magic_memo(
+                        new_game,
+                        new_bot_index,
+                        bots,
+                        magic_index,
+                        batch_config,
+                        memo,
+                    );
+                    child.add_illegal_moves(batch_config, illegal_moves);
+                    totals.add(&child);
+                    totals.think_times[bot_index].add(&think_times[bot_index]);
+                }
+                None => {
+                    let result = GameResult::new_forfeit(game.get_identities(), bot_index);
+                    totals.add_result(result, batch_config, illegal_moves, think_times);
+                }
             }
         }
     }
 
-    let mut final_result = GameResult::new(identities);
-    final_result.set_batch();
-    final_result.set_score1(total_score1 / count as f32);
-    final_result.set_score2(total_score2 / count as f32);
+    memo.insert(key, totals.clone());
+    totals
+}
 
-    if log_output {
-        let (bot1_name, bot2_name) = bot_factory.bot_names();
-        info!("{} WINS: {}", bot1_name, wins1);
-        info!("{} WINS: {}", bot2_name, wins2);
-        info!("DRAW/TIE: {}\n", num_draws);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::genbot3::gbot::{GenBot3, GenBot3Config};
+    use crate::bots::nbot1::nbot::NBot1Config;
+    use crate::bots::omnibot::obot::OmniBot;
+    use crate::engine::gameconfig::BotConfig;
+    use crate::engine::gameregistry::{get_games, GameOptions};
+    use crate::games::naughts::singlegame::NaughtsGame;
+    use serde_json::json;
+
+    fn get_magic_batch_config() -> BatchConfig {
+        BatchConfig {
+            batch_size: 0,
+            game: "naughts".parse().unwrap(),
+            magic: true,
+            magic_memo: false,
+            magic_depth: 0,
+            magic_samples: 0,
+            illegal_move_policy: IllegalMovePolicy::Snap,
+            illegal_move_penalty: 2.0,
+            move_time_limit: 0,
+            game_time_limit: 0,
+            timeout_action: TimeoutAction::RandomMove,
+            bot_config: BotConfig {
+                bot_names: [String::from("genbot3"), String::from("omnibot")],
+                bot_types: ["genbot3".parse().unwrap(), "omnibot".parse().unwrap()],
+                game: "naughts".parse().unwrap(),
+                recipe: serde_json::Value::Null,
+                move_time_limit: 0,
+                genbot3: GenBot3Config::default(),
+                nbot1: NBot1Config::default(),
+            },
+        }
+    }
 
-        info!("Average Scores:");
-        info!("{}: {:.3}", bot1_name, final_result.get_score1());
-        info!("{}: {:.3}", bot2_name, final_result.get_score2());
+    #[test]
+    fn test_magic_memo_matches_all() {
+        let mut batch_config = get_magic_batch_config();
+        batch_config.magic_memo = true;
+        let game = NaughtsGame::new();
+        let game_info = game.get_game_info();
+        let genbot: DynBot = Box::new(GenBot3::new(&game_info));
+        let omnibot: DynBot = Box::new(OmniBot::new(&game_info));
+        let mut bots1 = [genbot.clone_box(), omnibot.clone_box()];
+        let mut bots2 = [genbot, omnibot];
+
+        let mut stats = TreeStats::default();
+        let all = run_magic_all(game.clone_box(), &mut bots1, 1, &batch_config, &mut stats);
+        let mut memo = HashMap::new();
+        let memoized = run_magic_memo(game.clone_box(), 0, &mut bots2, 1, &batch_config, &mut memo);
+
+        assert_eq!(memoized.count, all.count);
+        assert_eq!(memoized.wins, all.wins);
+        assert_eq!(memoized.draws, all.draws);
+        assert_eq!(memoized.scores, all.scores);
+        assert_eq!(memoized.illegal_moves, all.illegal_moves);
     }
 
-    final_result
+    #[test]
+    fn test_magic_depth() {
+        let mut batch_config = get_magic_batch_config();
+        batch_config.magic_depth = 2;
+        let game = NaughtsGame::new();
+        let game_info = game.get_game_info();
+        let mut bots: [DynBot; 2] = [
+            Box::new(OmniBot::new(&game_info)),
+            Box::new(OmniBot::new(&game_info)),
+        ];
+
+        // Every position at depth 2 is played out to give exactly one game.
+        let mut stats = TreeStats::default();
+        let totals = run_magic_all(game.clone_box(), &mut bots, 0, &batch_config, &mut stats);
+        assert_eq!(totals.count, 72);
+        assert_eq!(stats.positions, vec![1, 9, 72]);
+    }
+
+    #[test]
+    fn test_tree_stats() {
+        let batch_config = get_magic_batch_config();
+        let game = NaughtsGame::new();
+        let game_info = game.get_game_info();
+        let mut bots: [DynBot; 2] = [
+            Box::new(OmniBot::new(&game_info)),
+            Box::new(OmniBot::new(&game_info)),
+        ];
+
+        // Every game of noughts and crosses is played.
+        let mut stats = TreeStats::default();
+        let totals = run_magic_all(game.clone_box(), &mut bots, 0, &batch_config, &mut stats);
+        assert_eq!(totals.count, 255_168);
+        assert_eq!(totals.wins, [131_184, 77_904]);
+        assert_eq!(totals.draws, 46_080);
+        assert_eq!(stats.positions.iter().sum::<u64>(), 549_946);
+        assert_eq!(stats.positions[..4], [1, 9, 72, 504]);
+        assert_eq!(stats.expanded.iter().sum::<u64>(), 549_946 - 255_168);
+        assert_eq!(
+            stats.children.iter().sum::<u64>(),
+            549_945,
+            "Every position except the first is a child"
+        );
+    }
+
+    /// Saved states that each registered game must reject, with its default options.
+    fn get_invalid_states(game: &str) -> Vec<serde_json::Value> {
+        match game {
+            "checkers" => vec![
+                json!({ "board": { "data": "x" } }),
+                json!({ "board": { "data": "k".repeat(32) } }),
+                json!({ "board": { "data": "x".repeat(32) }, "next_index": 2 }),
+                json!({ "board": { "data": "x".repeat(32) }, "next_index": 1, "jumping_piece": 0 }),
+            ],
+            "connect4" => vec![json!({ "data": "X" }), json!({ "data": "-".repeat(49) })],
+            "dots" => vec![
+                json!({ "grid": { "width": 0, "height": 1, "lines": [], "boxes": [] } }),
+                json!({
+                    "grid": { "width": 2, "height": 1, "lines": vec![false; 6], "boxes": [null, null] }
+                }),
+                json!({
+                    "grid": { "width": 2, "height": 1, "lines": vec![false; 7], "boxes": [null, 2] }
+                }),
+            ],
+            "hex" => vec![
+                json!({ "board": { "data": "" } }),
+                json!({ "board": { "data": "X".repeat(48) } }),
+                json!({ "board": { "data": format!("XO-{}", " ".repeat(46)) } }),
+                json!({ "board": { "data": " ".repeat(49) }, "num_turns": [1] }),
+            ],
+            "kalah" => vec![
+                json!({ "board": { "data": [] } }),
+                json!({ "board": { "data": [1, 2, 3] } }),
+                json!({ "board": { "data": [1, 0, -1, 0] } }),
+            ],
+            "naughts" => vec![
+                json!({ "data": "" }),
+                json!({ "data": "X" }),
+                json!({ "data": "XO-------X" }),
+                json!({ "data": "XO--?----" }),
+                json!({ "data": 5 }),
+            ],
+            "nim" => vec![
+                json!({ "heaps": [3, 4] }),
+                json!({ "heaps": [3, 4, 6] }),
+                json!({ "heaps": [3, 4, 5], "last_player": 2 }),
+            ],
+            "ultimate" => vec![
+                json!({ "boards": [] }),
+                json!({ "boards": vec![json!({ "data": "---------" }); 9], "active_board": 9 }),
+                json!({ "boards": vec![json!({ "data": "X" }); 9] }),
+            ],
+            _ => panic!("No invalid states for game {}", game),
+        }
+    }
+
+    #[test]
+    fn test_invalid_state() {
+        for spec in get_games() {
+            let mut game = spec.create(&GameOptions::new()).unwrap()();
+            let state = game.to_json();
+            let mut invalid = get_invalid_states(&spec.name);
+            invalid.push(json!({}));
+            for data in invalid.iter() {
+                match game.from_json(data) {
+                    Err(NaughtsError::InvalidState(_)) => {}
+                    x => panic!("{} gave {:?} for {}", spec.name, x, data),
+                }
+                assert_eq!(
+                    game.to_json(),
+                    state,
+                    "{} state was left unchanged",
+                    spec.name
+                );
+            }
+        }
+    }
 }
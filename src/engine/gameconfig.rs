@@ -1,25 +1,237 @@
-use argparse::{ArgumentParser, Store, StoreTrue};
+use argparse::{ArgumentParser, Collect, List, Store, StoreTrue};
+use std::env;
+use std::fmt;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::str::FromStr;
 
+use crate::bots::genbot3::gbot::GenBot3Config;
+use crate::bots::nbot1::nbot::NBot1Config;
 use crate::engine::botdb::BotDB;
 use crate::engine::botfactory::BotType;
-use crate::engine::gamefactory::GameType;
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+use crate::engine::experiment::{
+    find_config_arg, BatchSection, ExperimentConfig, GeneticSection, NimSection,
+};
+use crate::engine::gamefactory::{create_game_factory, GameType};
 use crate::engine::log;
+use crate::engine::recipe::{parse_recipe_str, RecipeEnvelope};
 
 /// Exit with the specified error message.
-fn exit_with_error(err: &str) {
-    println!("{}", err);
+fn exit_with_error(err: &str) -> ! {
+    eprintln!("{}", err);
     exit(1);
 }
 
+/// Print each command and its description, one per line.
+fn print_commands() {
+    println!("\nCommands:");
+    for (_, name, description) in COMMANDS.iter() {
+        println!("  {:<12}{}", name, description);
+    }
+    println!("\nUse '<command> --help' to see the options for each command.");
+}
+
+/// Default number of games per batch for the batch, evolve and tournament commands.
+const DEFAULT_BATCH_SIZE: u32 = 100;
+
+/// Default local port for the web command.
+const DEFAULT_WEB_PORT: u16 = 8080;
+
 #[derive(Debug, Clone)]
 pub enum RunMode {
     Single,
     Batch,
     Genetic,
+    Serve,
+    Web,
+    Tournament,
+    ListBots,
+    ListGames,
+}
+
+/// The CLI commands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Play,
+    Batch,
+    Magic,
+    Evolve,
+    Tournament,
+    Serve,
+    Web,
+    ListBots,
+    ListGames,
+}
+
+/// Each command with its name and a short description, in the order shown in --help.
+const COMMANDS: [(Command, &str, &str); 9] = [
+    (Command::Play, "play", "Play one game between 2 bots"),
+    (
+        Command::Batch,
+        "batch",
+        "Play a batch of games between 2 bots",
+    ),
+    (
+        Command::Magic,
+        "magic",
+        "Play every possible game between 2 bots (magic batch)",
+    ),
+    (
+        Command::Evolve,
+        "evolve",
+        "Evolve a genetic bot over many generations",
+    ),
+    (
+        Command::Tournament,
+        "tournament",
+        "Play a round robin between several bots",
+    ),
+    (
+        Command::Serve,
+        "serve",
+        "Let another program play a bot over stdin/stdout",
+    ),
+    (Command::Web, "web", "Play against a bot in the browser"),
+    (Command::ListBots, "list-bots", "List the available bots"),
+    (Command::ListGames, "list-games", "List the available games"),
+];
+
+impl Command {
+    pub fn name(self) -> &'static str {
+        COMMANDS.iter().find(|x| x.0 == self).map_or("", |x| x.1)
+    }
+
+    pub fn description(self) -> &'static str {
+        COMMANDS.iter().find(|x| x.0 == self).map_or("", |x| x.2)
+    }
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match COMMANDS.iter().find(|x| x.1 == value) {
+            Some(x) => Ok(x.0),
+            None => Err(format!(
+                "Unknown command '{}'. Expected one of: {}",
+                value,
+                COMMANDS
+                    .iter()
+                    .map(|x| x.1)
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+/// Parse a comma-separated list of numbers, e.g. "3,4,5".
+fn parse_number_list(value: &str) -> Result<Vec<u32>, String> {
+    value
+        .split(',')
+        .map(|x| {
+            x.trim()
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid number '{}' in list '{}'", x, value))
+        })
+        .collect()
+}
+
+/// Load a saved bot recipe from a file. See RecipeEnvelope::from_json() for supported formats.
+pub fn load_recipe_file(path: &Path) -> NaughtsResult<serde_json::Value> {
+    let recipe_str = fs::read_to_string(path).map_err(|e| {
+        NaughtsError::Io(format!(
+            "Error reading recipe file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    parse_recipe_str(&recipe_str).map_err(|e| match e {
+        NaughtsError::RecipeParse(x) => {
+            NaughtsError::RecipeParse(format!("{} in recipe file {}", x, path.display()))
+        }
+        x => x,
+    })
+}
+
+/// What to do when a bot returns a move that is not in the list of available moves.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IllegalMovePolicy {
+    /// Use the available move numerically closest to the bot's output.
+    Snap,
+    /// The bot immediately forfeits the game.
+    Forfeit,
+    /// Ask the bot again, up to the specified number of times, then forfeit.
+    Retry(u32),
+}
+
+impl FromStr for IllegalMovePolicy {
+    type Err = String;
+
+    /// Parse "snap", "forfeit", "retry" or "retry:N".
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let lower = value.to_ascii_lowercase();
+        match lower.split_once(':') {
+            None if lower == "snap" => Ok(IllegalMovePolicy::Snap),
+            None if lower == "forfeit" => Ok(IllegalMovePolicy::Forfeit),
+            None if lower == "retry" => Ok(IllegalMovePolicy::Retry(1)),
+            Some(("retry", n)) => n
+                .parse()
+                .map(IllegalMovePolicy::Retry)
+                .map_err(|_| format!("Invalid retry count: {}", n)),
+            _ => Err(format!(
+                "Unknown illegal move policy '{}'. Expected snap, forfeit or retry:N",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for IllegalMovePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IllegalMovePolicy::Snap => write!(f, "snap"),
+            IllegalMovePolicy::Forfeit => write!(f, "forfeit"),
+            IllegalMovePolicy::Retry(n) => write!(f, "retry:{}", n),
+        }
+    }
+}
+
+/// What to do when a bot takes longer than the time limit to choose a move.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimeoutAction {
+    /// Play a random available move instead of the bot's move.
+    RandomMove,
+    /// The bot immediately forfeits the game.
+    Forfeit,
+}
+
+impl FromStr for TimeoutAction {
+    type Err = String;
+
+    /// Parse "random" or "forfeit".
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "random" => Ok(TimeoutAction::RandomMove),
+            "forfeit" => Ok(TimeoutAction::Forfeit),
+            _ => Err(format!(
+                "Unknown timeout action '{}'. Expected random or forfeit",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TimeoutAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutAction::RandomMove => write!(f, "random"),
+            TimeoutAction::Forfeit => write!(f, "forfeit"),
+        }
+    }
 }
 
 /// The config required to construct bots.
@@ -29,6 +241,10 @@ pub struct BotConfig {
     pub bot_types: [BotType; 2],
     pub game: GameType,
     pub recipe: serde_json::Value,
+    /// Passed to bots that can enforce the move time limit themselves, such as external bots.
+    pub move_time_limit: u64,
+    pub genbot3: GenBot3Config,
+    pub nbot1: NBot1Config,
 }
 
 /// The config required to process one batch.
@@ -37,6 +253,20 @@ pub struct BatchConfig {
     pub batch_size: u32,
     pub game: GameType,
     pub magic: bool,
+    /// Memoize magic batch results per position instead of visiting every path.
+    pub magic_memo: bool,
+    /// Play out magic batch positions at this depth instead of branching. 0 means no limit.
+    pub magic_depth: u32,
+    /// Follow at most this many randomly chosen moves per magic position. 0 means all moves.
+    pub magic_samples: u32,
+    pub illegal_move_policy: IllegalMovePolicy,
+    /// Subtracted from a bot's game score for each illegal move it attempts.
+    pub illegal_move_penalty: f32,
+    /// Maximum time in milliseconds for a bot to choose one move. 0 means no limit.
+    pub move_time_limit: u64,
+    /// Maximum total time in milliseconds for a bot's moves in one game. 0 means no limit.
+    pub game_time_limit: u64,
+    pub timeout_action: TimeoutAction,
     pub bot_config: BotConfig,
 }
 
@@ -47,6 +277,15 @@ pub struct GeneticConfig {
     pub num_samples: u32,
     pub keep_samples: u32,
     pub wild_samples: u32,
+    /// Port for the live status endpoint. 0 means off.
+    pub status_port: u16,
+    /// Number of worker threads running batches.
+    pub num_threads: u32,
+    /// How far the score threshold moves towards a new best score each generation.
+    /// Lifting it slowly avoids getting stuck after a lucky fluke.
+    pub lifting_factor: f32,
+    /// Where to write scores.csv.
+    pub output_dir: PathBuf,
     pub batch_config: BatchConfig,
 }
 
@@ -55,8 +294,9 @@ pub struct GameConfig {
     pub game: GameType,
     pub silent: bool,
     pub console_logging: bool,
-    pub batch_mode: bool,
-    pub genetic_mode: bool,
+    pub command: Command,
+    /// Port for the web UI.
+    pub web_port: u16,
     pub no_batch_summary: bool,
     pub run_mode: RunMode,
     pub botdb: bool,
@@ -64,12 +304,33 @@ pub struct GameConfig {
     log_base_dir: PathBuf,
     data_base_dir: PathBuf,
     bot_names: [String; 2],
+    bot_types: [BotType; 2],
+    /// All bots entered in a tournament. The first 2 are also in bot_names.
+    tournament_bots: Vec<String>,
+    /// Only list bots for this game.
+    game_filter: Option<GameType>,
     batch_size: u32,
     magic: bool,
+    magic_memo: bool,
+    magic_depth: u32,
+    magic_samples: u32,
+    illegal_move_policy: IllegalMovePolicy,
+    illegal_move_penalty: f32,
+    move_time_limit: u64,
+    game_time_limit: u64,
+    timeout_action: TimeoutAction,
     num_generations: u32,
     num_samples: u32,
     keep_samples: u32,
     wild_samples: u32,
+    status_port: u16,
+    num_threads: u32,
+    lifting_factor: f32,
+    output_dir: PathBuf,
+    genbot3: GenBot3Config,
+    nbot1: NBot1Config,
+    botid: String,
+    recipe_file: String,
 }
 
 impl GameConfig {
@@ -80,24 +341,46 @@ impl GameConfig {
         let mut data_base_dir = path.clone();
         data_base_dir.push("data");
 
+        let output_dir = path.clone();
+
+        let random_bot = BotType::from_str("randombot").expect("randombot is a built-in bot");
         GameConfig {
             path,
-            game: GameType::Unknown,
+            game: GameType::from_str("naughts").expect("naughts is a built-in game"),
             silent: false,
             console_logging: false,
-            batch_mode: false,
-            genetic_mode: false,
+            command: Command::Play,
+            web_port: DEFAULT_WEB_PORT,
             no_batch_summary: false,
             run_mode: RunMode::Single,
             log_base_dir,
             data_base_dir,
             bot_names: [String::new(), String::new()],
+            bot_types: [random_bot.clone(), random_bot],
+            tournament_bots: Vec::new(),
+            game_filter: None,
             batch_size: 1,
             magic: false,
+            magic_memo: false,
+            magic_depth: 0,
+            magic_samples: 0,
+            illegal_move_policy: IllegalMovePolicy::Snap,
+            illegal_move_penalty: 0.0,
+            move_time_limit: 0,
+            game_time_limit: 0,
+            timeout_action: TimeoutAction::RandomMove,
             num_generations: 0,
             num_samples: 0,
             keep_samples: 0,
             wild_samples: 0,
+            status_port: 0,
+            num_threads: 6,
+            lifting_factor: 0.2,
+            output_dir,
+            genbot3: GenBot3Config::default(),
+            nbot1: NBot1Config::default(),
+            botid: String::new(),
+            recipe_file: String::new(),
             botrecipe: serde_json::Value::Null,
             botdb: false,
         }
@@ -107,158 +390,573 @@ impl GameConfig {
     pub fn init(&mut self) {
         self.parse_args();
         self.sanitise_args();
-        self.init_logging().expect("Error setting up logging");
-
-        if self.genetic_mode {
-            self.run_mode = RunMode::Genetic;
-        } else if self.batch_mode {
-            self.run_mode = RunMode::Batch;
-        } else {
-            self.run_mode = RunMode::Single;
+        if let Err(e) = self.init_logging() {
+            exit_with_error(&format!("Error setting up logging: {}", e));
         }
+
+        self.run_mode = match self.command {
+            Command::Play => RunMode::Single,
+            Command::Batch | Command::Magic => RunMode::Batch,
+            Command::Evolve => RunMode::Genetic,
+            Command::Tournament => RunMode::Tournament,
+            Command::Serve => RunMode::Serve,
+            Command::Web => RunMode::Web,
+            Command::ListBots => RunMode::ListBots,
+            Command::ListGames => RunMode::ListGames,
+        };
     }
 
     /// Parse CLI args.
+    ///
+    /// The first arg is the command, and the remaining args depend on the command.
+    /// If --config is specified, that file is loaded first so that CLI args override it.
     fn parse_args(&mut self) {
-        let mut game = String::new();
-        let mut bot1 = String::new();
-        let mut bot2 = String::new();
-        let mut botid = String::new();
-        let mut recipefile = String::new();
+        let mut command_name = String::new();
+        let mut args: Vec<String> = Vec::new();
 
         {
             let mut ap = ArgumentParser::new();
-            ap.set_description("Naughts");
-            ap.refer(&mut bot1)
-                .add_argument("bot1", Store, "First bot, e.g. 'human'")
+            ap.set_description("Naughts: a sandbox for AI bots playing turn-based games.");
+            ap.refer(&mut command_name)
+                .add_argument("command", Store, "The command to run, from the list below")
                 .required();
+            ap.refer(&mut args)
+                .add_argument("arguments", List, "Arguments for the command");
+            ap.stop_on_first_argument(true);
 
-            ap.refer(&mut bot2)
-                .add_argument("bot2", Store, "Second bot")
-                .required();
+            // argparse reflows the description, so the command list is printed after the help.
+            match ap.parse(env::args().collect(), &mut io::stdout(), &mut io::stderr()) {
+                Ok(()) => {}
+                Err(0) => {
+                    print_commands();
+                    exit(0);
+                }
+                Err(x) => exit(x),
+            }
+        }
 
-            ap.refer(&mut game)
-                .add_option(&["--game"], Store, "The game to run")
-                .required();
-            ap.refer(&mut self.batch_size).add_option(
-                &["--batch"],
-                Store,
-                "Batch mode. Specify the number of games to run per batch",
-            );
-            ap.refer(&mut self.magic).add_option(
-                &["--magic"],
-                StoreTrue,
-                "Magic batch mode. Run all possible games against this bot.",
-            );
-            ap.refer(&mut self.num_generations).add_option(
-                &["--genetic"],
-                Store,
-                "Genetic mode. Specify number of generations to run (Requires --batch or --magic)",
-            );
-            ap.refer(&mut self.num_samples).add_option(
-                &["--samples"],
-                Store,
-                "Number of samples per generation. (Requires --genetic)",
-            );
-            ap.refer(&mut self.keep_samples).add_option(
-                &["--keep"],
-                Store,
-                "Number of winning samples to 'keep' (Requires --genetic)",
-            );
-            ap.refer(&mut self.wild_samples).add_option(
-                &["--wild"],
-                Store,
-                "Number of 'wild' (fresh, randomly generated) samples to include \
-                 in each generation",
-            );
-            ap.refer(&mut botid)
-                .add_option(&["--botid"], Store, "The botID to load from botdb");
-            ap.refer(&mut self.botdb).add_option(
-                &["--botdb"],
-                StoreTrue,
-                "Use BotDB to store recipes",
-            );
-            ap.refer(&mut recipefile).add_option(
-                &["--botrecipe"],
-                Store,
-                "Filename to load recipe from",
+        let command = Command::from_str(&command_name).unwrap_or_else(|e| exit_with_error(&e));
+        self.command = command;
+        args.insert(0, format!("naughts {}", command.name()));
+
+        if let Command::Batch | Command::Evolve | Command::Tournament = command {
+            self.batch_size = DEFAULT_BATCH_SIZE;
+        }
+
+        let experiment = match find_config_arg(&args) {
+            Some(x) => ExperimentConfig::load(Path::new(&x))
+                .unwrap_or_else(|e| exit_with_error(&e.to_string())),
+            None => ExperimentConfig::default(),
+        };
+        self.apply_experiment_config(&experiment);
+
+        let join_numbers = |x: &Vec<u32>| {
+            x.iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        let mut bots = experiment.bots.unwrap_or_default();
+        if bots.len() > 2 && command != Command::Tournament {
+            exit_with_error(
+                "Only the tournament command accepts more than 2 bots in a config file",
             );
-            ap.parse_args_or_exit();
+        }
+        let mut game = experiment.game.unwrap_or_default();
+        let mut bot1 = bots.first().cloned().unwrap_or_default();
+        let mut bot2 = bots.get(1).cloned().unwrap_or_default();
+        let mut heaps = experiment
+            .nim
+            .heaps
+            .map_or(String::new(), |x| join_numbers(&x));
+        let mut misere = experiment.nim.misere.unwrap_or(false);
+        let mut subtract = experiment
+            .nim
+            .subtract
+            .map_or(String::new(), |x| join_numbers(&x));
+        let experiment_options = experiment.game_options.unwrap_or_default();
+        let mut game_options: Vec<String> = Vec::new();
+        let mut illegal = experiment.batch.illegal.unwrap_or_default();
+        let mut timeout = experiment.batch.timeout.unwrap_or_default();
+        let mut output_dir = experiment.output_dir.unwrap_or_default();
+        let mut config_file = String::new();
+
+        let is_game_command = !matches!(command, Command::ListBots | Command::ListGames);
+        {
+            let mut ap = ArgumentParser::new();
+            ap.set_description(command.description());
+            match command {
+                Command::Play | Command::Batch | Command::Magic | Command::Evolve => {
+                    ap.refer(&mut bot1).add_argument(
+                        "bot1",
+                        Store,
+                        "First bot, e.g. 'genbot3'. Use list-bots to see all bots",
+                    );
+                    ap.refer(&mut bot2)
+                        .add_argument("bot2", Store, "Second bot");
+                }
+                Command::Serve => {
+                    ap.refer(&mut bot1)
+                        .add_argument("bot", Store, "The bot that chooses moves");
+                }
+                Command::Web => {
+                    ap.refer(&mut bot1).add_argument(
+                        "bot",
+                        Store,
+                        "The default opponent (default: randombot)",
+                    );
+                }
+                Command::Tournament => {
+                    ap.refer(&mut bots).add_argument(
+                        "bots",
+                        List,
+                        "The bots to enter (at least 2)",
+                    );
+                }
+                Command::ListBots | Command::ListGames => {}
+            }
+
+            if command == Command::ListBots {
+                ap.refer(&mut game).add_option(
+                    &["--game"],
+                    Store,
+                    "Only list bots that can play this game",
+                );
+            } else if is_game_command {
+                ap.refer(&mut game)
+                    .add_option(&["--game"], Store, "The game to play (required)");
+            }
+
+            if is_game_command {
+                ap.refer(&mut config_file).add_option(
+                    &["--config"],
+                    Store,
+                    "TOML or JSON experiment file. Options on the command line override it",
+                );
+                ap.refer(&mut game_options).add_option(
+                    &["--game-option"],
+                    Collect,
+                    "Game option as name=value, e.g. 'size=9' for hex. Use list-games to see them",
+                );
+                ap.refer(&mut heaps).add_option(
+                    &["--heaps"],
+                    Store,
+                    "Nim heap sizes, e.g. '3,4,5'. Same as --game-option 'heaps=[3,4,5]'",
+                );
+                ap.refer(&mut misere).add_option(
+                    &["--misere"],
+                    StoreTrue,
+                    "Nim misere play. Same as --game-option misere=true",
+                );
+                ap.refer(&mut subtract).add_option(
+                    &["--subtract"],
+                    Store,
+                    "Nim counts that may be taken, e.g. '1,2'. Same as --game-option 'subtract=[1,2]'",
+                );
+            }
+
+            if let Command::Play
+            | Command::Batch
+            | Command::Magic
+            | Command::Evolve
+            | Command::Serve = command
+            {
+                ap.refer(&mut self.botid).add_option(
+                    &["--botid"],
+                    Store,
+                    "The botID to load from botdb",
+                );
+                ap.refer(&mut self.recipe_file).add_option(
+                    &["--botrecipe"],
+                    Store,
+                    "Filename to load recipe from",
+                );
+            }
+
+            if let Command::Play
+            | Command::Batch
+            | Command::Magic
+            | Command::Evolve
+            | Command::Tournament = command
+            {
+                ap.refer(&mut illegal).add_option(
+                    &["--illegal"],
+                    Store,
+                    "What to do when a bot makes an illegal move: snap (default), forfeit or \
+                     retry:N",
+                );
+                ap.refer(&mut self.illegal_move_penalty).add_option(
+                    &["--illegal-penalty"],
+                    Store,
+                    "Score penalty applied for each illegal move a bot attempts",
+                );
+                ap.refer(&mut self.move_time_limit).add_option(
+                    &["--move-time"],
+                    Store,
+                    "Maximum time in milliseconds for a bot to choose one move. Checked once the \
+                     bot returns its move: only external bots are stopped at the limit",
+                );
+                ap.refer(&mut self.game_time_limit).add_option(
+                    &["--game-time"],
+                    Store,
+                    "Maximum total time in milliseconds for a bot's moves in one game. With \
+                     --timeout random, a bot that runs out plays the rest of the game randomly",
+                );
+                ap.refer(&mut timeout).add_option(
+                    &["--timeout"],
+                    Store,
+                    "What to do when a bot exceeds a time limit: random (default) or forfeit",
+                );
+            }
+
+            if let Command::Batch | Command::Evolve | Command::Tournament = command {
+                ap.refer(&mut self.batch_size).add_option(
+                    &["--size"],
+                    Store,
+                    "Number of games per batch (default 100)",
+                );
+            }
+
+            if command == Command::Evolve {
+                ap.refer(&mut self.magic).add_option(
+                    &["--magic"],
+                    StoreTrue,
+                    "Score each sample with a magic batch instead of --size games",
+                );
+            }
+
+            if let Command::Magic | Command::Evolve = command {
+                ap.refer(&mut self.magic_memo).add_option(
+                    &["--memo"],
+                    StoreTrue,
+                    "Magic batch: reuse results for positions reached by different move orders",
+                );
+                ap.refer(&mut self.magic_depth).add_option(
+                    &["--magic-depth"],
+                    Store,
+                    "Magic batch: play out unfinished games at this depth instead of branching",
+                );
+                ap.refer(&mut self.magic_samples).add_option(
+                    &["--magic-samples"],
+                    Store,
+                    "Magic batch: follow at most this many random moves per magic position",
+                );
+            }
+
+            if command == Command::Evolve {
+                ap.refer(&mut self.num_generations).add_option(
+                    &["--generations"],
+                    Store,
+                    "Number of generations to run (required)",
+                );
+                ap.refer(&mut self.num_samples).add_option(
+                    &["--samples"],
+                    Store,
+                    "Number of samples per generation",
+                );
+                ap.refer(&mut self.keep_samples).add_option(
+                    &["--keep"],
+                    Store,
+                    "Number of winning samples to 'keep'",
+                );
+                ap.refer(&mut self.wild_samples).add_option(
+                    &["--wild"],
+                    Store,
+                    "Number of 'wild' (fresh, randomly generated) samples to include \
+                     in each generation",
+                );
+                ap.refer(&mut self.num_threads).add_option(
+                    &["--threads"],
+                    Store,
+                    "Number of threads running batches (default 6)",
+                );
+                ap.refer(&mut self.status_port).add_option(
+                    &["--status-port"],
+                    Store,
+                    "Serve live JSON status for the run on this local port",
+                );
+                ap.refer(&mut output_dir).add_option(
+                    &["--output-dir"],
+                    Store,
+                    "Where to write scores.csv and the effective config (default: the exe dir)",
+                );
+                ap.refer(&mut self.botdb).add_option(
+                    &["--botdb"],
+                    StoreTrue,
+                    "Use BotDB to store recipes",
+                );
+            }
+
+            if command == Command::Web {
+                ap.refer(&mut self.web_port).add_option(
+                    &["--port"],
+                    Store,
+                    "Local port for the web UI (default 8080)",
+                );
+            }
+
+            if let Err(x) = ap.parse(args, &mut io::stdout(), &mut io::stderr()) {
+                exit(x);
+            }
+        }
+
+        if !game.is_empty() {
+            match GameType::from_str(&game) {
+                Ok(x) if command == Command::ListBots => self.game_filter = Some(x),
+                Ok(x) => self.game = x,
+                Err(x) => exit_with_error(&x),
+            }
+        } else if is_game_command {
+            exit_with_error("Option --game is required");
+        }
+
+        if !is_game_command {
+            return;
+        }
+
+        match command {
+            Command::Serve if bot1.is_empty() => exit_with_error("Argument bot is required"),
+            Command::Web if bot1.is_empty() => bot1 = String::from("randombot"),
+            Command::Tournament => {
+                if bots.len() < 2 {
+                    exit_with_error("A tournament requires at least 2 bots");
+                }
+                bot1 = bots[0].clone();
+                bot2 = bots[1].clone();
+                self.tournament_bots = bots;
+            }
+            _ => {}
+        }
+
+        // Serve and web modes only use the first bot. Reuse it so that the bot config is complete.
+        if let Command::Serve | Command::Web = command {
+            bot2 = bot1.clone();
+        }
+
+        if bot1.is_empty() || bot2.is_empty() {
+            exit_with_error("Arguments bot1 and bot2 are required");
         }
 
         self.bot_names[0] = bot1;
         self.bot_names[1] = bot2;
-        self.game = GameType::from(game);
-
-        if !botid.is_empty() {
-            match BotDB::new().load_bot(botid.as_str()) {
-                Ok(x) => self.botrecipe = x,
-                Err(x) => exit_with_error(format!("Failed to load bot: {}", x).as_str()),
-            };
-        } else if !recipefile.is_empty() {
-            let recipe_str =
-                fs::read_to_string(Path::new(&recipefile)).expect("Error reading botrecipe file");
-            if recipe_str.starts_with('{') {
-                // Parse JSON.
-                self.botrecipe = serde_json::from_str(&recipe_str).expect("Invalid JSON format");
-            } else {
-                self.botrecipe = serde_json::json!({ "recipe": recipe_str });
+        for (index, name) in self.bot_names.iter().enumerate() {
+            match BotType::from_str(name).and_then(|x| x.check_game(&self.game).map(|_| x)) {
+                Ok(x) => self.bot_types[index] = x,
+                Err(x) => exit_with_error(&x),
+            }
+        }
+        for name in &self.tournament_bots {
+            if let Err(x) = BotType::from_str(name).and_then(|x| x.check_game(&self.game)) {
+                exit_with_error(&x);
             }
         }
-    }
 
-    /// Sanitise CLI args into sane defaults and catch errors.
-    fn sanitise_args(&mut self) {
-        // Tidy up default args.
-        if self.batch_size > 1 {
-            self.batch_mode = true;
+        if !output_dir.is_empty() {
+            self.output_dir = PathBuf::from(output_dir);
         }
 
-        if self.num_generations > 0 {
-            self.genetic_mode = true;
+        if !illegal.is_empty() {
+            match IllegalMovePolicy::from_str(&illegal) {
+                Ok(x) => self.illegal_move_policy = x,
+                Err(x) => exit_with_error(&x),
+            }
         }
 
-        if !self.batch_mode && !self.magic {
-            if self.genetic_mode {
-                exit_with_error("Option --genetic requires --batch");
+        if !timeout.is_empty() {
+            match TimeoutAction::from_str(&timeout) {
+                Ok(x) => self.timeout_action = x,
+                Err(x) => exit_with_error(&x),
             }
-            if self.num_samples > 0 {
-                exit_with_error("Option --samples requires --batch");
+        }
+
+        // Options from the config file come first, so that the command line overrides them.
+        let mut options: Vec<(String, serde_json::Value)> =
+            experiment_options.into_iter().collect();
+        for option in game_options.iter() {
+            match option.split_once('=') {
+                Some((name, value)) => {
+                    // Values that aren't valid JSON are strings.
+                    let value = serde_json::from_str(value)
+                        .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+                    options.push((name.trim().to_string(), value));
+                }
+                None => exit_with_error(&format!(
+                    "Invalid game option '{}'. Expected name=value",
+                    option
+                )),
             }
-            if self.keep_samples > 0 {
-                exit_with_error("Option --keep requires --batch");
+        }
+
+        // The Nim flags are shorthand for game options, so they are checked like any other.
+        if !heaps.is_empty() {
+            match parse_number_list(&heaps) {
+                Ok(x) => options.push((String::from("heaps"), serde_json::json!(x))),
+                Err(x) => exit_with_error(&x),
+            }
+        }
+        if !subtract.is_empty() {
+            match parse_number_list(&subtract) {
+                Ok(x) => options.push((String::from("subtract"), serde_json::json!(x))),
+                Err(x) => exit_with_error(&x),
             }
-            if self.wild_samples > 0 {
-                exit_with_error("Option --wild requires --batch");
+        }
+        if misere {
+            options.push((String::from("misere"), serde_json::json!(true)));
+        }
+
+        for (name, value) in options {
+            if let Err(x) = self.game.set_option(&name, value) {
+                exit_with_error(&x.to_string());
             }
         }
 
-        if !self.genetic_mode {
-            if self.num_samples > 0 {
-                exit_with_error("Option --samples requires --genetic");
+        let recipe = if !self.botid.is_empty() {
+            match BotDB::new().and_then(|mut x| x.load_bot(self.botid.as_str())) {
+                Ok(x) => Some(x),
+                Err(x) => exit_with_error(format!("Failed to load bot: {}", x).as_str()),
             }
-            if self.keep_samples > 0 {
-                exit_with_error("Option --keep requires --genetic");
+        } else if !self.recipe_file.is_empty() {
+            match load_recipe_file(Path::new(&self.recipe_file)) {
+                Ok(x) => Some(x),
+                Err(x) => exit_with_error(&x.to_string()),
             }
-            if self.wild_samples > 0 {
-                exit_with_error("Option --wild requires --genetic");
+        } else {
+            None
+        };
+
+        if let Some(x) = recipe {
+            match self.unwrap_recipe(&x) {
+                Ok(x) => self.botrecipe = x,
+                Err(x) => exit_with_error(&x.to_string()),
             }
         }
+    }
 
-        if self.magic {
-            if self.batch_mode {
-                exit_with_error("Cannot specify --batch with --magic");
+    /// Check a saved recipe against the bots and game, and get the bot's own JSON from it.
+    fn unwrap_recipe(&self, data: &serde_json::Value) -> NaughtsResult<serde_json::Value> {
+        let envelope = RecipeEnvelope::from_json(data)?;
+        let bot_type = self
+            .bot_names
+            .iter()
+            .chain(self.tournament_bots.iter())
+            .filter_map(|x| BotType::from_str(x).ok())
+            .find(|x| x.to_string() == envelope.bot)
+            .ok_or_else(|| {
+                NaughtsError::RecipeParse(format!(
+                    "Recipe is for {}, which is not playing",
+                    envelope.bot
+                ))
+            })?;
+        let game_info = create_game_factory(&self.game)().get_game_info();
+        envelope.check(&bot_type, &self.game, &game_info)?;
+        Ok(envelope.payload)
+    }
+
+    /// Copy values from an experiment file. Values that need parsing are handled by parse_args().
+    fn apply_experiment_config(&mut self, experiment: &ExperimentConfig) {
+        if let Some(ref x) = experiment.botid {
+            self.botid = x.clone();
+        }
+        if let Some(ref x) = experiment.recipe {
+            self.recipe_file = x.clone();
+        }
+
+        let batch = &experiment.batch;
+        self.batch_size = batch.size.unwrap_or(self.batch_size);
+        self.magic = batch.magic.unwrap_or(self.magic);
+        self.magic_memo = batch.memo.unwrap_or(self.magic_memo);
+        self.magic_depth = batch.magic_depth.unwrap_or(self.magic_depth);
+        self.magic_samples = batch.magic_samples.unwrap_or(self.magic_samples);
+        self.illegal_move_penalty = batch.illegal_penalty.unwrap_or(self.illegal_move_penalty);
+        self.move_time_limit = batch.move_time.unwrap_or(self.move_time_limit);
+        self.game_time_limit = batch.game_time.unwrap_or(self.game_time_limit);
+
+        let genetic = &experiment.genetic;
+        self.num_generations = genetic.generations.unwrap_or(self.num_generations);
+        self.num_samples = genetic.samples.unwrap_or(self.num_samples);
+        self.keep_samples = genetic.keep.unwrap_or(self.keep_samples);
+        self.wild_samples = genetic.wild.unwrap_or(self.wild_samples);
+        self.num_threads = genetic.threads.unwrap_or(self.num_threads);
+        self.lifting_factor = genetic.lifting_factor.unwrap_or(self.lifting_factor);
+        self.status_port = genetic.status_port.unwrap_or(self.status_port);
+
+        if let Some(ref x) = experiment.genbot3 {
+            self.genbot3 = x.clone();
+        }
+        if let Some(ref x) = experiment.nbot1 {
+            self.nbot1 = x.clone();
+        }
+    }
+
+    /// Sanitise CLI args into sane defaults and catch errors.
+    fn sanitise_args(&mut self) {
+        // Ignore config file values that don't apply to this command.
+        match self.command {
+            Command::Play | Command::Serve | Command::Web => {
+                self.batch_size = 1;
+                self.magic = false;
+                self.num_generations = 0;
+            }
+            Command::Batch | Command::Tournament => {
+                self.magic = false;
+                self.num_generations = 0;
+            }
+            Command::Magic => {
+                self.magic = true;
+                self.num_generations = 0;
             }
+            Command::Evolve => {
+                if self.num_generations == 0 {
+                    exit_with_error("Option --generations is required");
+                }
+            }
+            Command::ListBots | Command::ListGames => return,
+        }
+
+        if self.batch_size == 0 && !self.magic {
+            exit_with_error("Option --size must be at least 1");
+        }
+
+        if !self.magic && (self.magic_memo || self.magic_depth > 0 || self.magic_samples > 0) {
+            exit_with_error(
+                "Options --memo, --magic-depth and --magic-samples require magic batches",
+            );
+        }
+
+        if self.magic_memo && (self.magic_depth > 0 || self.magic_samples > 0) {
+            exit_with_error("Option --memo cannot be used with --magic-depth or --magic-samples");
+        }
+
+        // Memoized games aren't replayed, so time limits can't be checked on them.
+        if self.magic_memo && (self.move_time_limit > 0 || self.game_time_limit > 0) {
+            exit_with_error("Option --memo cannot be used with --move-time or --game-time");
+        }
 
-            // Magic flag implicitly enables batch mode.
+        if self.num_threads == 0 {
+            exit_with_error("Option --threads must be at least 1");
+        }
+        if self.lifting_factor <= 0.0 || self.lifting_factor > 1.0 {
+            exit_with_error("Genetic lifting_factor must be greater than 0 and at most 1");
+        }
+        if self.genbot3.num_nodes == 0 {
+            exit_with_error("GenBot3 num_nodes must be at least 1");
+        }
+        if self.genbot3.num_output_inputs == 0 {
+            exit_with_error("GenBot3 num_output_inputs must be at least 1");
+        }
+        if self.nbot1.mutation_range <= 0.0 {
+            exit_with_error("NBot1 mutation_range must be greater than 0");
+        }
+
+        if self.magic {
+            // Magic batches play every possible game, so the batch size is not used.
             self.batch_size = 0;
-            self.batch_mode = true;
         }
 
-        if self.magic || self.batch_mode {
+        if self.magic || self.batch_size > 1 {
             self.silent = true;
 
-            if self.genetic_mode {
+            if self.command == Command::Evolve {
                 self.no_batch_summary = true;
             }
         }
@@ -276,35 +974,51 @@ impl GameConfig {
 
         let mut log_filename = self.log_base_dir.clone();
         log_filename.push("naughts.log");
-        log::setup_logger(log_filename.as_path());
-
-        Ok(())
+        log::setup_logger(log_filename.as_path(), self.command == Command::Serve)
     }
 
     /// Get the base data path. Bots should clone this and add the appropriate subdir.
     pub fn get_data_path(&self) -> &Path {
-        &self.data_base_dir.as_path()
+        self.data_base_dir.as_path()
     }
 
     /// Get the bot config.
     pub fn get_bot_config(&self) -> BotConfig {
         BotConfig {
             bot_names: [self.bot_names[0].clone(), self.bot_names[1].clone()],
-            bot_types: [
-                BotType::from(self.bot_names[0].clone()),
-                BotType::from(self.bot_names[1].clone()),
-            ],
+            bot_types: self.bot_types.clone(),
             game: self.game.clone(),
             recipe: self.botrecipe.clone(),
+            move_time_limit: self.move_time_limit,
+            genbot3: self.genbot3.clone(),
+            nbot1: self.nbot1.clone(),
         }
     }
 
+    /// Get the names of all bots entered in a tournament.
+    pub fn get_tournament_bots(&self) -> &[String] {
+        &self.tournament_bots
+    }
+
+    /// Get the game to list bots for, if any.
+    pub fn get_game_filter(&self) -> Option<&GameType> {
+        self.game_filter.as_ref()
+    }
+
     /// Get the batch config.
     pub fn get_batch_config(&self) -> BatchConfig {
         BatchConfig {
             batch_size: self.batch_size,
             game: self.game.clone(),
             magic: self.magic,
+            magic_memo: self.magic_memo,
+            magic_depth: self.magic_depth,
+            magic_samples: self.magic_samples,
+            illegal_move_policy: self.illegal_move_policy.clone(),
+            illegal_move_penalty: self.illegal_move_penalty,
+            move_time_limit: self.move_time_limit,
+            game_time_limit: self.game_time_limit,
+            timeout_action: self.timeout_action.clone(),
             bot_config: self.get_bot_config(),
         }
     }
@@ -317,7 +1031,82 @@ impl GameConfig {
             num_samples: self.num_samples,
             keep_samples: self.keep_samples,
             wild_samples: self.wild_samples,
+            status_port: self.status_port,
+            num_threads: self.num_threads,
+            lifting_factor: self.lifting_factor,
+            output_dir: self.output_dir.clone(),
             batch_config: self.get_batch_config(),
         }
     }
+
+    /// Get the effective config, combining the config file, CLI args and defaults.
+    pub fn get_experiment_config(&self) -> ExperimentConfig {
+        let non_empty = |x: &str| {
+            if x.is_empty() {
+                None
+            } else {
+                Some(x.to_string())
+            }
+        };
+        ExperimentConfig {
+            game: Some(self.game.to_string()),
+            game_options: if self.game.get_options().is_empty() {
+                None
+            } else {
+                Some(self.game.get_options().clone())
+            },
+            bots: if self.tournament_bots.is_empty() {
+                Some(self.bot_names.to_vec())
+            } else {
+                Some(self.tournament_bots.clone())
+            },
+            botid: non_empty(&self.botid),
+            recipe: non_empty(&self.recipe_file),
+            output_dir: Some(self.output_dir.to_string_lossy().into_owned()),
+            batch: BatchSection {
+                size: Some(self.batch_size),
+                magic: Some(self.magic),
+                memo: Some(self.magic_memo),
+                magic_depth: Some(self.magic_depth),
+                magic_samples: Some(self.magic_samples),
+                illegal: Some(self.illegal_move_policy.to_string()),
+                illegal_penalty: Some(self.illegal_move_penalty),
+                move_time: Some(self.move_time_limit),
+                game_time: Some(self.game_time_limit),
+                timeout: Some(self.timeout_action.to_string()),
+            },
+            genetic: GeneticSection {
+                generations: Some(self.num_generations),
+                samples: Some(self.num_samples),
+                keep: Some(self.keep_samples),
+                wild: Some(self.wild_samples),
+                threads: Some(self.num_threads),
+                lifting_factor: Some(self.lifting_factor),
+                status_port: Some(self.status_port),
+            },
+            nim: NimSection::default(),
+            genbot3: Some(self.genbot3.clone()),
+            nbot1: Some(self.nbot1.clone()),
+        }
+    }
+
+    /// Save the effective config as experiment.json in the output dir, so that the run can be
+    /// repeated with --config.
+    ///
+    /// Only the evolve command saves it, since it is the only command with an output dir.
+    /// Batch, magic and tournament runs don't write any files, and are repeated by running the
+    /// same command line.
+    pub fn save_experiment_config(&self) -> NaughtsResult<PathBuf> {
+        fs::create_dir_all(&self.output_dir).map_err(|e| {
+            NaughtsError::Io(format!(
+                "Error creating output dir {}: {}",
+                self.output_dir.display(),
+                e
+            ))
+        })?;
+        let mut path = self.output_dir.clone();
+        path.push("experiment.json");
+        self.get_experiment_config().save(&path)?;
+        Ok(path)
+    }
 }
@@ -1,34 +1,93 @@
-use crate::engine::gamebase::GameTrait;
-use crate::games::connect4::singlegame::Connect4Game;
-use crate::games::naughts::singlegame::NaughtsGame;
-
-#[derive(Debug, Clone)]
-pub enum GameType {
-    Connect4,
-    Naughts,
-    Unknown,
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gamebase::{GameInfo, GameTrait};
+use crate::engine::gameregistry::{find_game, get_games, GameOptions, GameSpec};
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// A registered game, plus its options.
+///
+/// Games are added with register_game().
+#[derive(Clone)]
+pub struct GameType {
+    spec: GameSpec,
+    options: GameOptions,
+    factory: GameFactory,
 }
 
-impl<S> From<S> for GameType
-where
-    S: Into<String>,
-{
-    fn from(value: S) -> Self {
-        let svalue: String = value.into();
-        match svalue.to_ascii_lowercase().as_str() {
-            "naughts" => GameType::Naughts,
-            "connect4" => GameType::Connect4,
-            _ => GameType::Unknown,
-        }
+impl GameType {
+    pub fn get_spec(&self) -> &GameSpec {
+        &self.spec
+    }
+
+    pub fn get_options(&self) -> &GameOptions {
+        &self.options
+    }
+
+    /// Get the input and output counts with the current options.
+    pub fn get_game_info(&self) -> GameInfo {
+        (self.factory)().get_game_info()
+    }
+
+    /// Set a game option, e.g. "size" for hex. The game is left unchanged if the option
+    /// is invalid.
+    pub fn set_option(&mut self, name: &str, value: serde_json::Value) -> NaughtsResult<()> {
+        let mut options = self.options.clone();
+        options.insert(name.to_string(), value);
+        self.factory = self.spec.create(&options)?;
+        self.options = options;
+        Ok(())
     }
 }
 
-pub type GameFactory = fn() -> Box<dyn GameTrait>;
+impl FromStr for GameType {
+    type Err = String;
 
-pub fn create_game_factory(game: &GameType) -> GameFactory {
-    match game {
-        GameType::Connect4 => || Box::new(Connect4Game::new()),
-        GameType::Naughts => || Box::new(NaughtsGame::new()),
-        GameType::Unknown => panic!("Unknown game type"),
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let spec = find_game(value).ok_or_else(|| {
+            let names: Vec<String> = get_games().into_iter().map(|x| x.name).collect();
+            format!(
+                "Unknown game '{}'. Expected one of: {}",
+                value,
+                names.join(", ")
+            )
+        })?;
+        let options = GameOptions::new();
+        let factory = spec.create(&options).map_err(|e| e.to_string())?;
+        Ok(GameType {
+            spec,
+            options,
+            factory,
+        })
+    }
+}
+
+impl fmt::Display for GameType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.spec.name)
+    }
+}
+
+impl fmt::Debug for GameType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GameType")
+            .field("name", &self.spec.name)
+            .field("options", &self.options)
+            .finish()
     }
 }
+
+/// Games are equal if they have the same name and options.
+impl PartialEq for GameType {
+    fn eq(&self, other: &Self) -> bool {
+        self.spec.name == other.spec.name && self.options == other.options
+    }
+}
+
+/// Creates a new game. This is a closure so that it can carry game options (e.g. Nim heaps).
+pub type GameFactory = Arc<dyn Fn() -> Box<dyn GameTrait> + Send + Sync>;
+
+pub fn create_game_factory(game: &GameType) -> GameFactory {
+    game.factory.clone()
+}
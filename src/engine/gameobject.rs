@@ -1,6 +1,63 @@
+use serde::de::DeserializeOwned;
 use serde_json;
 
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+
 pub trait GameObject {
     fn to_json(&self) -> serde_json::Value;
-    fn from_json(&mut self, data: &serde_json::Value);
+
+    /// Load from JSON. Invalid data is rejected, leaving the object unchanged.
+    fn from_json(&mut self, data: &serde_json::Value) -> NaughtsResult<()>;
+}
+
+/// Check the "version" field of saved data. Data without a version is treated as version 1.
+pub fn check_version(data: &serde_json::Value, name: &str, latest: u64) -> NaughtsResult<()> {
+    match data.get("version") {
+        None => Ok(()),
+        Some(x) => match x.as_u64() {
+            Some(v) if v >= 1 && v <= latest => Ok(()),
+            _ => Err(NaughtsError::RecipeParse(format!(
+                "Unsupported {} version {} (latest supported is {})",
+                name, x, latest
+            ))),
+        },
+    }
+}
+
+/// Get a field of saved state, or None if it isn't set.
+pub fn get_state_field<T: DeserializeOwned>(
+    data: &serde_json::Value,
+    name: &str,
+    field: &str,
+) -> NaughtsResult<Option<T>> {
+    match data.get(field) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(x) => serde_json::from_value(x.clone())
+            .map(Some)
+            .map_err(|e| invalid_state(name, format!("{}: {}", field, e))),
+    }
+}
+
+/// Get a field of saved state that must be set.
+pub fn require_state_field<T: DeserializeOwned>(
+    data: &serde_json::Value,
+    name: &str,
+    field: &str,
+) -> NaughtsResult<T> {
+    get_state_field(data, name, field)?
+        .ok_or_else(|| invalid_state(name, format!("{} is missing", field)))
+}
+
+/// Get the saved state of one part of the game, e.g. the board.
+pub fn require_state_part<'a>(
+    data: &'a serde_json::Value,
+    name: &str,
+    field: &str,
+) -> NaughtsResult<&'a serde_json::Value> {
+    data.get(field)
+        .ok_or_else(|| invalid_state(name, format!("{} is missing", field)))
+}
+
+pub fn invalid_state(name: &str, msg: String) -> NaughtsError {
+    NaughtsError::InvalidState(format!("Invalid {} state: {}", name, msg))
 }
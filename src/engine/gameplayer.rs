@@ -1,7 +1,7 @@
 use crate::engine::gameobject::GameObject;
-use crate::engine::gameresult::{GameScore, NULL_SCORE};
+use crate::engine::gameresult::{GameResult, GameScore, NULL_SCORE};
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct PlayerData {
     pub name: String,
     pub identity: char,
@@ -13,6 +13,9 @@ pub struct PlayerData {
 }
 
 pub trait GamePlayer: GameObject + Send {
+    /// Create a boxed copy of this bot, including all current state.
+    fn clone_box(&self) -> Box<dyn GamePlayer>;
+
     fn get_data(&self) -> &PlayerData;
     fn get_data_mut(&mut self) -> &mut PlayerData;
 
@@ -33,6 +36,21 @@ pub trait GamePlayer: GameObject + Send {
         available_moves.to_vec()
     }
 
+    /// Called with the result at the end of each game.
+    fn game_over(&mut self, _result: &GameResult) {}
+
+    /// Return true if the bot can no longer play, e.g. an external process has crashed.
+    /// The engine treats this as a forfeit.
+    fn has_failed(&self) -> bool {
+        false
+    }
+
+    /// Return true if the bot gave up waiting for its last move, e.g. an external process
+    /// didn't answer in time. The engine treats this as exceeding the move time limit.
+    fn has_timed_out(&self) -> bool {
+        false
+    }
+
     fn get_name(&self) -> &str {
         self.get_data().name.as_str()
     }
@@ -70,6 +88,11 @@ pub trait GamePlayer: GameObject + Send {
         self.get_data().is_genetic
     }
 
+    /// Return true if process() always returns the same move for the same inputs.
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+
     fn label(&self) -> String {
         format!("{} {}", self.get_name(), self.get_identity())
     }
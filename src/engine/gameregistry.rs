@@ -0,0 +1,291 @@
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::engine::botregistry::ParamSpec;
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+use crate::engine::gamebase::{GameInfo, GameTrait};
+use crate::engine::gamefactory::GameFactory;
+use crate::games::checkers::singlegame::{CheckersGame, DEFAULT_DRAW_LIMIT};
+use crate::games::connect4::singlegame::Connect4Game;
+use crate::games::dots::singlegame::{DotsGame, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+use crate::games::hex::singlegame::{HexGame, DEFAULT_SIZE};
+use crate::games::kalah::singlegame::{KalahGame, DEFAULT_PITS, DEFAULT_SEEDS};
+use crate::games::naughts::singlegame::NaughtsGame;
+use crate::games::nim::singlegame::{NimConfig, NimGame};
+use crate::games::ultimate::singlegame::UltimateGame;
+
+/// Game options by name, e.g. { "size": 9 } for hex.
+pub type GameOptions = serde_json::Map<String, serde_json::Value>;
+
+/// Creates a game factory from the game options. Options that aren't set use their defaults.
+pub type GameConstructor = Arc<dyn Fn(&GameOptions) -> NaughtsResult<GameFactory> + Send + Sync>;
+
+/// A game that can be chosen by name.
+#[derive(Clone)]
+pub struct GameSpec {
+    pub name: String,
+    pub description: String,
+    pub params: Vec<ParamSpec>,
+    /// The identities of each player, with the default options.
+    pub identities: [char; 2],
+    /// The input and output counts, with the default options.
+    pub game_info: GameInfo,
+    constructor: GameConstructor,
+}
+
+impl GameSpec {
+    /// Create a game spec. The constructor must succeed with the default options.
+    pub fn new<F>(name: &str, description: &str, constructor: F) -> Self
+    where
+        F: Fn(&GameOptions) -> NaughtsResult<GameFactory> + Send + Sync + 'static,
+    {
+        let game_factory = constructor(&GameOptions::new())
+            .unwrap_or_else(|e| panic!("Game {} failed with the default options: {}", name, e));
+        let game = game_factory();
+        GameSpec {
+            name: name.to_ascii_lowercase(),
+            description: description.to_string(),
+            params: Vec::new(),
+            identities: game.get_identities(),
+            game_info: game.get_game_info(),
+            constructor: Arc::new(constructor),
+        }
+    }
+
+    /// Create a game spec for a game with no options.
+    pub fn without_options<F, G>(name: &str, description: &str, new_game: F) -> Self
+    where
+        F: Fn() -> G + Send + Sync + 'static,
+        G: GameTrait + 'static,
+    {
+        let new_game = Arc::new(new_game);
+        GameSpec::new(name, description, move |_| {
+            let new_game = new_game.clone();
+            Ok(Arc::new(move || Box::new(new_game())))
+        })
+    }
+
+    pub fn with_param(mut self, name: &str, description: &str, default: serde_json::Value) -> Self {
+        self.params.push(ParamSpec {
+            name: name.to_string(),
+            description: description.to_string(),
+            default,
+        });
+        self
+    }
+
+    /// Create a game factory, checking that every option is one of the params.
+    pub fn create(&self, options: &GameOptions) -> NaughtsResult<GameFactory> {
+        for name in options.keys() {
+            if !self.params.iter().any(|x| x.name == *name) {
+                let names: Vec<&str> = self.params.iter().map(|x| x.name.as_str()).collect();
+                return Err(NaughtsError::Config(format!(
+                    "Game {} has no option '{}'. Options: {}",
+                    self.name,
+                    name,
+                    if names.is_empty() {
+                        String::from("none")
+                    } else {
+                        names.join(", ")
+                    }
+                )));
+            }
+        }
+        (self.constructor)(options)
+    }
+}
+
+impl fmt::Debug for GameSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GameSpec")
+            .field("name", &self.name)
+            .field("params", &self.params)
+            .field("identities", &self.identities)
+            .field("game_info", &self.game_info)
+            .finish()
+    }
+}
+
+/// Get the value of a game option, or None if it isn't set.
+pub fn get_option<T: DeserializeOwned>(
+    options: &GameOptions,
+    name: &str,
+) -> NaughtsResult<Option<T>> {
+    match options.get(name) {
+        Some(x) => serde_json::from_value(x.clone()).map(Some).map_err(|e| {
+            NaughtsError::Config(format!("Invalid value for game option {}: {}", name, e))
+        }),
+        None => Ok(None),
+    }
+}
+
+/// Get a positive size option, e.g. a board size.
+fn get_size(options: &GameOptions, name: &str, default: usize) -> NaughtsResult<usize> {
+    match get_option(options, name)? {
+        Some(0) => Err(NaughtsError::Config(format!(
+            "Game option {} must be at least 1",
+            name
+        ))),
+        Some(x) => Ok(x),
+        None => Ok(default),
+    }
+}
+
+/// Get a positive count option, e.g. a number of turns.
+fn get_count(options: &GameOptions, name: &str, default: u32) -> NaughtsResult<u32> {
+    match get_option(options, name)? {
+        Some(0) => Err(NaughtsError::Config(format!(
+            "Game option {} must be at least 1",
+            name
+        ))),
+        Some(x) => Ok(x),
+        None => Ok(default),
+    }
+}
+
+fn create_nim(options: &GameOptions) -> NaughtsResult<GameFactory> {
+    let mut config = NimConfig::default();
+    if let Some(x) = get_option::<Vec<u32>>(options, "heaps")? {
+        if !x.iter().any(|h| *h > 0) {
+            return Err(NaughtsError::Config(String::from(
+                "Game option heaps requires at least 1 non-empty heap",
+            )));
+        }
+        config.heaps = x;
+    }
+    if let Some(x) = get_option(options, "misere")? {
+        config.misere = x;
+    }
+    if let Some(x) = get_option::<Vec<u32>>(options, "subtract")? {
+        if !x.iter().any(|c| *c > 0) {
+            return Err(NaughtsError::Config(String::from(
+                "Game option subtract requires a non-zero count",
+            )));
+        }
+        config.subtraction_set = Some(x);
+    }
+    Ok(Arc::new(move || {
+        Box::new(NimGame::with_config(config.clone()))
+    }))
+}
+
+fn builtin_games() -> Vec<GameSpec> {
+    let nim = NimConfig::default();
+    vec![
+        GameSpec::new("checkers", "Checkers (English draughts)", |x| {
+            let draw_limit = get_count(x, "draw_limit", DEFAULT_DRAW_LIMIT)?;
+            Ok(Arc::new(move || {
+                Box::new(CheckersGame::with_draw_limit(draw_limit))
+            }))
+        })
+        .with_param(
+            "draw_limit",
+            "Number of turns without a capture before the game is drawn",
+            serde_json::json!(DEFAULT_DRAW_LIMIT),
+        ),
+        GameSpec::without_options("connect4", "Connect 4", Connect4Game::new),
+        GameSpec::new("dots", "Dots and Boxes", |x| {
+            let width = get_size(x, "width", DEFAULT_WIDTH)?;
+            let height = get_size(x, "height", DEFAULT_HEIGHT)?;
+            Ok(Arc::new(move || {
+                Box::new(DotsGame::with_size(width, height))
+            }))
+        })
+        .with_param(
+            "width",
+            "Number of boxes across",
+            serde_json::json!(DEFAULT_WIDTH),
+        )
+        .with_param(
+            "height",
+            "Number of boxes down",
+            serde_json::json!(DEFAULT_HEIGHT),
+        ),
+        GameSpec::new("hex", "Hex", |x| {
+            let size = get_size(x, "size", DEFAULT_SIZE)?;
+            let swap_rule = get_option(x, "swap_rule")?.unwrap_or(true);
+            Ok(Arc::new(move || {
+                Box::new(HexGame::with_config(size, swap_rule))
+            }))
+        })
+        .with_param(
+            "size",
+            "Board width and height",
+            serde_json::json!(DEFAULT_SIZE),
+        )
+        .with_param(
+            "swap_rule",
+            "The second player may swap the first stone instead of moving",
+            serde_json::json!(true),
+        ),
+        GameSpec::new("kalah", "Kalah (mancala)", |x| {
+            let num_pits = get_size(x, "pits", DEFAULT_PITS)?;
+            let num_seeds = get_count(x, "seeds", DEFAULT_SEEDS)?;
+            Ok(Arc::new(move || {
+                Box::new(KalahGame::with_config(num_pits, num_seeds))
+            }))
+        })
+        .with_param(
+            "pits",
+            "Number of pits for each player",
+            serde_json::json!(DEFAULT_PITS),
+        )
+        .with_param(
+            "seeds",
+            "Number of seeds in each pit at the start",
+            serde_json::json!(DEFAULT_SEEDS),
+        ),
+        GameSpec::without_options("naughts", "Noughts and crosses", NaughtsGame::new),
+        GameSpec::new("nim", "Nim and subtraction games", create_nim)
+            .with_param("heaps", "Starting heap sizes", serde_json::json!(nim.heaps))
+            .with_param(
+                "misere",
+                "Misere play: whoever takes the last object loses",
+                serde_json::json!(nim.misere),
+            )
+            .with_param(
+                "subtract",
+                "Counts that may be taken from a heap (default: any)",
+                serde_json::json!(nim.subtraction_set),
+            ),
+        GameSpec::without_options(
+            "ultimate",
+            "Ultimate noughts and crosses",
+            UltimateGame::new,
+        ),
+    ]
+}
+
+fn registry() -> &'static RwLock<Vec<GameSpec>> {
+    static REGISTRY: OnceLock<RwLock<Vec<GameSpec>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(builtin_games()))
+}
+
+/// Add a game, so that it can be chosen by name like the built-in games.
+pub fn register_game(spec: GameSpec) -> NaughtsResult<()> {
+    let mut games = registry().write().expect("Game registry lock poisoned");
+    if games.iter().any(|x| x.name == spec.name) {
+        return Err(NaughtsError::Config(format!(
+            "A game named '{}' is already registered",
+            spec.name
+        )));
+    }
+    games.push(spec);
+    Ok(())
+}
+
+/// Find a registered game by name.
+pub fn find_game(name: &str) -> Option<GameSpec> {
+    let name = name.to_ascii_lowercase();
+    let games = registry().read().expect("Game registry lock poisoned");
+    games.iter().find(|x| x.name == name).cloned()
+}
+
+/// Get all registered games, in the order they were registered.
+pub fn get_games() -> Vec<GameSpec> {
+    registry()
+        .read()
+        .expect("Game registry lock poisoned")
+        .clone()
+}
@@ -1,14 +1,69 @@
-use serde::export::Formatter;
-use std::fmt;
+use std::fmt::{self, Formatter};
+use std::time::Duration;
 
 pub type GameScore = f32;
 pub static NULL_SCORE: GameScore = -999.0;
+/// The score given to a bot that forfeits a game. This is worse than losing normally.
+pub static FORFEIT_SCORE: GameScore = -1000.0;
+
+/// Time taken by one bot to choose its moves.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThinkTime {
+    pub num_moves: u32,
+    pub total: Duration,
+    pub max: Duration,
+    /// Number of moves that exceeded a time limit.
+    pub timeouts: u32,
+}
+
+impl ThinkTime {
+    /// Record the time taken for one move.
+    pub fn add_move(&mut self, elapsed: Duration) {
+        self.num_moves += 1;
+        self.total += elapsed;
+        if elapsed > self.max {
+            self.max = elapsed;
+        }
+    }
+
+    /// Combine with the times from another game.
+    pub fn add(&mut self, other: &ThinkTime) {
+        self.num_moves += other.num_moves;
+        self.total += other.total;
+        self.timeouts += other.timeouts;
+        if other.max > self.max {
+            self.max = other.max;
+        }
+    }
+
+    /// Get the mean time per move.
+    pub fn mean(&self) -> Duration {
+        if self.num_moves == 0 {
+            Duration::default()
+        } else {
+            self.total / self.num_moves
+        }
+    }
+}
+
+impl fmt::Display for ThinkTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "mean {:.3}ms, max {:.3}ms, timeouts {}",
+            self.mean().as_secs_f64() * 1000.0,
+            self.max.as_secs_f64() * 1000.0,
+            self.timeouts
+        )
+    }
+}
 
 /// Game status enum.
 enum GameStatus {
     Open,
     Win,
     Tie,
+    Forfeit,
     Batch,
 }
 
@@ -22,8 +77,11 @@ impl Default for GameStatus {
 /// A single game result.
 pub struct GameResult {
     scores: [GameScore; 2],
+    penalties: [GameScore; 2],
     identities: [char; 2],
     status: GameStatus,
+    illegal_moves: [u32; 2],
+    think_times: [ThinkTime; 2],
 }
 
 impl GameResult {
@@ -31,11 +89,22 @@ impl GameResult {
     pub fn new(identities: [char; 2]) -> Self {
         GameResult {
             scores: [0.0, 0.0],
+            penalties: [0.0, 0.0],
             identities,
             status: GameStatus::Open,
+            illegal_moves: [0, 0],
+            think_times: [ThinkTime::default(); 2],
         }
     }
 
+    /// Create a result where the specified player forfeited the game.
+    pub fn new_forfeit(identities: [char; 2], loser_index: usize) -> Self {
+        let mut result = GameResult::new(identities);
+        result.scores[loser_index] = FORFEIT_SCORE;
+        result.status = GameStatus::Forfeit;
+        result
+    }
+
     /// Set the score.
     pub fn set_score1(&mut self, score: GameScore) {
         self.scores[0] = score
@@ -46,15 +115,21 @@ impl GameResult {
         self.scores[1] = score
     }
 
-    /// Get the score.
+    /// Get the score, less any penalty.
     pub fn get_score1(&self) -> GameScore {
-        self.scores[0]
+        self.scores[0] - self.penalties[0]
     }
 
     pub fn get_score2(&self) -> GameScore {
-        self.scores[1]
+        self.scores[1] - self.penalties[1]
+    }
+
+    /// Set the penalty taken off each player's score. Penalties don't change who won the game.
+    pub fn set_penalties(&mut self, penalties: [GameScore; 2]) {
+        self.penalties = penalties;
     }
 
+    /// Get the index of the winner, based on the scores before any penalty.
     pub fn get_winner(&self) -> Option<usize> {
         if self.scores[0] > self.scores[1] {
             Some(0)
@@ -79,6 +154,26 @@ impl GameResult {
         self.status = GameStatus::Tie;
     }
 
+    /// Set the number of illegal moves attempted by each player.
+    pub fn set_illegal_moves(&mut self, illegal_moves: [u32; 2]) {
+        self.illegal_moves = illegal_moves;
+    }
+
+    /// Get the number of illegal moves attempted by each player.
+    pub fn get_illegal_moves(&self) -> [u32; 2] {
+        self.illegal_moves
+    }
+
+    /// Set the time taken by each player to choose their moves.
+    pub fn set_think_times(&mut self, think_times: [ThinkTime; 2]) {
+        self.think_times = think_times;
+    }
+
+    /// Get the time taken by each player to choose their moves.
+    pub fn get_think_times(&self) -> [ThinkTime; 2] {
+        self.think_times
+    }
+
     /// Set game status to Batch.
     pub fn set_batch(&mut self) {
         self.status = GameStatus::Batch;
@@ -92,6 +187,11 @@ impl GameResult {
         }
     }
 
+    /// Return true if one player forfeited the game.
+    pub fn is_forfeit(&self) -> bool {
+        matches!(self.status, GameStatus::Forfeit)
+    }
+
     /// Return true if game is a tie.
     pub fn is_tie(&self) -> bool {
         match self.status {
@@ -118,6 +218,10 @@ impl fmt::Display for GameResult {
                 Some(x) => write!(f, "RESULT: {} wins!", x),
                 None => write!(f, "RESULT: Tie"),
             },
+            GameStatus::Forfeit => match self.get_winner_identity() {
+                Some(x) => write!(f, "RESULT: {} wins by forfeit!", x),
+                None => write!(f, "RESULT: Forfeit"),
+            },
 
             GameStatus::Batch => write!(f, "BATCH RESULT: {:?}", self.scores),
         }
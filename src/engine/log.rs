@@ -4,7 +4,12 @@ use log::LevelFilter;
 use std::io;
 use std::path::Path;
 
-pub fn setup_logger(log_filename: &Path) {
+/// Log to the console and to the specified file.
+/// Console output goes to stderr if `use_stderr` is set, leaving stdout free for other uses.
+pub fn setup_logger(log_filename: &Path, use_stderr: bool) -> io::Result<()> {
+    let log_file = fern::log_file(log_filename)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", log_filename.display(), e)))?;
+
     let colors = ColoredLevelConfig::new()
         .trace(Color::Blue)
         .debug(Color::Yellow)
@@ -12,22 +17,27 @@ pub fn setup_logger(log_filename: &Path) {
         .warn(Color::Magenta)
         .error(Color::BrightMagenta);
 
+    let console =
+        fern::Dispatch::new()
+            .level(LevelFilter::Debug)
+            .format(move |out, message, record| {
+                out.finish(format_args!(
+                    "{color_line}{date} {level:7} {message}\x1B[0m",
+                    color_line =
+                        format_args!("\x1B[{}m", colors.get_color(&record.level()).to_fg_str()),
+                    date = chrono::Local::now().format("%H:%M:%S"),
+                    level = &record.level(),
+                    message = message
+                ))
+            });
+    let console = if use_stderr {
+        console.chain(io::stderr())
+    } else {
+        console.chain(io::stdout())
+    };
+
     fern::Dispatch::new()
-        .chain(
-            fern::Dispatch::new()
-                .level(LevelFilter::Debug)
-                .format(move |out, message, record| {
-                    out.finish(format_args!(
-                        "{color_line}{date} {level:7} {message}\x1B[0m",
-                        color_line =
-                            format_args!("\x1B[{}m", colors.get_color(&record.level()).to_fg_str()),
-                        date = chrono::Local::now().format("%H:%M:%S"),
-                        level = &record.level(),
-                        message = message
-                    ))
-                })
-                .chain(io::stdout()),
-        )
+        .chain(console)
         .chain(
             fern::Dispatch::new()
                 .level(LevelFilter::Debug)
@@ -39,8 +49,8 @@ pub fn setup_logger(log_filename: &Path) {
                         message
                     ))
                 })
-                .chain(fern::log_file(log_filename).expect("Error setting up log file")),
+                .chain(log_file),
         )
         .apply()
-        .expect("Error setting up logging");
+        .map_err(io::Error::other)
 }
@@ -0,0 +1,214 @@
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::botfactory::BotType;
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+use crate::engine::gamebase::GameInfo;
+use crate::engine::gamefactory::GameType;
+
+/// The latest recipe envelope format.
+pub const FORMAT_VERSION: u64 = 1;
+
+/// A saved bot recipe, with enough information to check it before loading it into a bot.
+///
+/// Recipes saved before the envelope existed were just the bot's JSON (or a bare GenBot3
+/// recipe string). These are migrated by from_json(), but don't record the game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecipeEnvelope {
+    pub format_version: u64,
+    /// The bot name, e.g. "genbot3".
+    pub bot: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_info: Option<GameInfo>,
+    /// Anything else worth keeping, e.g. score and generation.
+    #[serde(default)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+    /// The bot's own JSON, as returned by to_json().
+    pub payload: serde_json::Value,
+}
+
+impl RecipeEnvelope {
+    pub fn new(
+        bot_type: &BotType,
+        game: &GameType,
+        game_info: &GameInfo,
+        payload: serde_json::Value,
+    ) -> Self {
+        RecipeEnvelope {
+            format_version: FORMAT_VERSION,
+            bot: bot_type.to_string(),
+            game: Some(game.to_string()),
+            game_info: Some(game_info.clone()),
+            metadata: serde_json::Map::new(),
+            payload,
+        }
+    }
+
+    pub fn with_metadata(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.metadata.insert(key.to_string(), value);
+        self
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_else(|_| serde_json::json!({}))
+    }
+
+    /// Parse a saved recipe, migrating legacy formats.
+    pub fn from_json(data: &serde_json::Value) -> NaughtsResult<Self> {
+        let invalid = |msg: String| NaughtsError::RecipeParse(msg);
+        if let Some(version) = data.get("format_version") {
+            match version.as_u64() {
+                Some(x) if x > FORMAT_VERSION => {
+                    return Err(invalid(format!(
+                        "Unsupported recipe format version {} (latest supported is {})",
+                        x, FORMAT_VERSION
+                    )));
+                }
+                Some(_) => {}
+                None => {
+                    return Err(invalid(format!(
+                        "Invalid recipe format version: {}",
+                        version
+                    )))
+                }
+            }
+            return serde_json::from_value(data.clone())
+                .map_err(|e| invalid(format!("Invalid recipe envelope: {}", e)));
+        }
+
+        let (bot, payload) = match data {
+            serde_json::Value::String(x) => ("genbot3", serde_json::json!({ "recipe": x })),
+            x if x.get("recipe").is_some_and(|y| y.is_string()) => ("genbot3", x.clone()),
+            x if x.get("layers").is_some_and(|y| y.is_array()) => ("nbot1", x.clone()),
+            _ => {
+                return Err(invalid(String::from(
+                    "Unrecognised recipe: expected a recipe envelope, a GenBot3 recipe or \
+                     NBot1 layers",
+                )))
+            }
+        };
+        info!("Migrating legacy {} recipe", bot);
+        Ok(RecipeEnvelope {
+            format_version: FORMAT_VERSION,
+            bot: bot.to_string(),
+            game: None,
+            game_info: None,
+            metadata: serde_json::Map::new(),
+            payload,
+        })
+    }
+
+    /// Check that this recipe was saved by the specified bot, for the same game.
+    pub fn check(
+        &self,
+        bot_type: &BotType,
+        game: &GameType,
+        game_info: &GameInfo,
+    ) -> NaughtsResult<()> {
+        let bot = bot_type.to_string();
+        if self.bot != bot {
+            return Err(NaughtsError::RecipeParse(format!(
+                "Recipe is for {}, not {}",
+                self.bot, bot
+            )));
+        }
+
+        match self.game {
+            Some(ref x) if *x != game.to_string() => {
+                return Err(NaughtsError::RecipeParse(format!(
+                    "Recipe is for {}, not {}",
+                    x, game
+                )));
+            }
+            Some(_) => {}
+            None => warn!("Recipe does not specify a game. Assuming {}", game),
+        }
+
+        match self.game_info {
+            Some(ref x) if x != game_info => Err(NaughtsError::RecipeParse(format!(
+                "Recipe has {} inputs and {} outputs, but {} has {} and {}",
+                x.input_count, x.output_count, game, game_info.input_count, game_info.output_count
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parse the contents of a recipe file. Legacy files may contain a bare GenBot3 recipe string.
+pub fn parse_recipe_str(value: &str) -> NaughtsResult<serde_json::Value> {
+    match serde_json::from_str(value) {
+        Ok(x) => Ok(x),
+        Err(_) if value.trim_start().starts_with("NODE_") => {
+            Ok(serde_json::Value::String(value.trim().to_string()))
+        }
+        Err(e) => Err(NaughtsError::RecipeParse(format!("Invalid JSON: {}", e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let genbot3: BotType = "genbot3".parse().unwrap();
+        let nbot1: BotType = "nbot1".parse().unwrap();
+        let naughts: GameType = "naughts".parse().unwrap();
+        let game_info = GameInfo {
+            input_count: 18,
+            output_count: 9,
+        };
+        let envelope = RecipeEnvelope::new(
+            &nbot1,
+            &naughts,
+            &game_info,
+            serde_json::json!({ "layers": [] }),
+        )
+        .with_metadata("score", serde_json::json!(1.5));
+        let loaded = RecipeEnvelope::from_json(&envelope.to_json()).unwrap();
+        assert_eq!(loaded, envelope);
+        loaded.check(&nbot1, &naughts, &game_info).unwrap();
+
+        assert!(loaded.check(&genbot3, &naughts, &game_info).is_err());
+        assert!(loaded
+            .check(&nbot1, &"connect4".parse().unwrap(), &game_info)
+            .is_err());
+        assert!(loaded
+            .check(&nbot1, &naughts, &GameInfo::default())
+            .is_err());
+
+        let mut future = envelope.to_json();
+        future["format_version"] = serde_json::json!(FORMAT_VERSION + 1);
+        assert!(RecipeEnvelope::from_json(&future).is_err());
+    }
+
+    #[test]
+    fn test_legacy_migration() {
+        let legacy = [
+            (
+                parse_recipe_str("NODE_INPUT,NODE_NOT:0\n").unwrap(),
+                "genbot3",
+            ),
+            (
+                serde_json::json!({ "recipe": "NODE_INPUT,NODE_NOT:0" }),
+                "genbot3",
+            ),
+            (serde_json::json!({ "layers": [] }), "nbot1"),
+        ];
+        for (data, bot) in legacy.iter() {
+            let envelope = RecipeEnvelope::from_json(data).unwrap();
+            assert_eq!(envelope.bot, *bot);
+            assert_eq!(envelope.game, None);
+        }
+        assert_eq!(
+            RecipeEnvelope::from_json(&legacy[0].0).unwrap().payload,
+            legacy[1].0
+        );
+
+        assert!(RecipeEnvelope::from_json(&serde_json::json!({ "nodes": [] })).is_err());
+        assert!(parse_recipe_str("not a recipe").is_err());
+    }
+}
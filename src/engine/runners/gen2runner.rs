@@ -29,24 +29,27 @@ Later expansion:
 -- With some tweaks this bot could run indefinitely which would be great for the online
    one.
 
--- TODO: add rust web server to run the rust version on the website.
+-- Use --status-port to watch progress (and grab the best recipe) while it runs.
 */
 
 use crate::engine::botfactory::BotFactory;
+use crate::engine::errors::{NaughtsError, NaughtsResult};
 use crate::engine::gamebase::run_batch;
 use crate::engine::gameconfig::GameConfig;
 use crate::engine::gamefactory::create_game_factory;
 use crate::engine::gameresult::GameScore;
+use crate::engine::recipe::RecipeEnvelope;
 
 use log::*;
 
-use std::error::Error;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
-use std::{env, thread};
+use std::thread;
+use tiny_http::{Header, Method, Response, Server};
 
 #[derive(Debug, Clone)]
 struct BotSample {
@@ -95,17 +98,28 @@ struct BotStack {
     child_limit: u32,
     best_score: Option<GameScore>,
     best_recipe: Option<serde_json::Value>,
+    best_generation: u32,
+    max_generation: u32,
+    num_samples: u64,
+    started: Instant,
     scores_csv: Option<File>,
+    /// Saved recipes use this envelope, with the payload replaced.
+    envelope: RecipeEnvelope,
 }
 
 impl BotStack {
-    pub fn new(child_limit: u32, scores_csv: Option<File>) -> Self {
+    pub fn new(child_limit: u32, scores_csv: Option<File>, envelope: RecipeEnvelope) -> Self {
         Self {
             stack: Vec::new(),
             child_limit,
             best_score: None,
             best_recipe: None,
+            best_generation: 0,
+            max_generation: 0,
+            num_samples: 0,
+            started: Instant::now(),
             scores_csv,
+            envelope,
         }
     }
 
@@ -114,6 +128,11 @@ impl BotStack {
     }
 
     pub fn next(&mut self, last_result: BotSample) -> Option<BotSample> {
+        if last_result.score.is_some() {
+            self.num_samples += 1;
+            self.max_generation = self.max_generation.max(last_result.generation);
+        }
+
         let mut sample = match self.stack.pop() {
             Some(x) => x,
             None => {
@@ -140,12 +159,20 @@ impl BotStack {
                             "Found new high score: {:.3} (Generation: {})",
                             x, last_result.generation
                         );
+                        let mut envelope = self.envelope.clone();
+                        envelope.payload = last_result.recipe.clone();
+                        let best_recipe = envelope
+                            .with_metadata("score", serde_json::json!(x))
+                            .with_metadata("generation", serde_json::json!(last_result.generation))
+                            .to_json();
                         self.best_score = last_result.score;
-                        self.best_recipe = Some(last_result.recipe.clone());
+                        self.best_generation = last_result.generation;
                         if let Some(x) = &mut self.scores_csv {
-                            writeln!(x, "{}", last_result.recipe.to_string())
-                                .expect("Error writing scores.csv");
+                            if let Err(e) = writeln!(x, "{}", best_recipe) {
+                                error!("Error writing scores.csv: {}", e);
+                            }
                         }
+                        self.best_recipe = Some(best_recipe);
                     }
                 }
 
@@ -166,9 +193,67 @@ impl BotStack {
             Some(sample)
         }
     }
+
+    /// Get the current progress as JSON.
+    pub fn get_status(&self) -> serde_json::Value {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let samples_per_sec = if elapsed > 0.0 {
+            self.num_samples as f64 / elapsed
+        } else {
+            0.0
+        };
+        serde_json::json!({
+            "generation": self.stack.last().map(|x| x.generation),
+            "max_generation": self.max_generation,
+            "stack_depth": self.stack.len(),
+            "samples": self.num_samples,
+            "samples_per_sec": samples_per_sec,
+            "elapsed_secs": elapsed,
+            "best_score": self.best_score,
+            "best_generation": self.best_generation,
+            "best_recipe": self.best_recipe
+        })
+    }
 }
 
-pub fn gen2_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
+/// Serve the status of a running evolution on localhost, without interrupting it.
+///
+/// GET /status returns the progress as JSON, and GET /best returns just the best recipe
+/// (suitable for --botrecipe).
+fn start_status_server(port: u16, bot_stack: Arc<Mutex<BotStack>>) -> NaughtsResult<()> {
+    let address = format!("127.0.0.1:{}", port);
+    let server = Server::http(&address)
+        .map_err(|e| NaughtsError::Io(format!("Error starting server: {}", e)))?;
+    info!("Status available at http://{}/status", address);
+
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let (status, value) = {
+                let stack = bot_stack.lock().expect("Bot stack lock poisoned");
+                match (request.method(), request.url()) {
+                    (Method::Get, "/") | (Method::Get, "/status") => (200, stack.get_status()),
+                    (Method::Get, "/best") => match stack.best_recipe {
+                        Some(ref x) => (200, x.clone()),
+                        None => (404, serde_json::json!({ "error": "No recipe scored yet" })),
+                    },
+                    _ => (404, serde_json::json!({ "error": "Not found" })),
+                }
+            };
+
+            let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                .expect("Invalid header");
+            let response = Response::from_string(value.to_string())
+                .with_status_code(status)
+                .with_header(header);
+            if let Err(x) = request.respond(response) {
+                warn!("Error sending status: {}", x);
+            }
+        }
+    });
+    Ok(())
+}
+
+pub fn gen2_runner(config: GameConfig) -> NaughtsResult<()> {
     let botrecipe = &config.botrecipe;
     if !botrecipe.is_null() {
         info!("Loaded recipe from BotDB");
@@ -178,9 +263,9 @@ pub fn gen2_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
     let game_factory = create_game_factory(&genetic_config.game);
     let game = game_factory();
     let game_info = game.get_game_info();
-    let mut bot_factory = BotFactory::new(game_info, config.get_bot_config());
-    let (bot1, bot2) = bot_factory.create_bots();
-    let bots = vec![bot1, bot2];
+    let mut bot_factory = BotFactory::new(game_info.clone(), config.get_bot_config());
+    let (bot1, bot2) = bot_factory.create_bots()?;
+    let bots = [bot1, bot2];
 
     let genetic_index = if bots[0].is_genetic() {
         if bots[1].is_genetic() {
@@ -192,57 +277,65 @@ pub fn gen2_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
         }
 
         0
-    } else {
-        assert!(bots[1].is_genetic(), "Neither bot is a genetic bot!");
+    } else if bots[1].is_genetic() {
         1
+    } else {
+        return Err(NaughtsError::Config(String::from(
+            "Neither bot is a genetic bot, e.g. genbot3",
+        )));
     };
 
     bot_factory.set_genetic_index(genetic_index);
+    let experiment_path = config.save_experiment_config()?;
+    info!("Saved config to {}", experiment_path.display());
+
     let scores_file = {
-        let scores_path = match env::current_exe() {
-            Ok(x) => {
-                let mut p = x.parent().expect("Error getting parent dir").to_path_buf();
-                p.push("scores.csv");
-                p
-            }
-            _ => panic!("Error getting current exe path"),
-        };
-        Some(
-            OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(scores_path)
-                .expect("Error opening scores.csv"),
-        )
+        let mut scores_path = genetic_config.output_dir.clone();
+        scores_path.push("scores.csv");
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&scores_path)
+            .map_err(|e| {
+                NaughtsError::Io(format!("Error opening {}: {}", scores_path.display(), e))
+            })?;
+        Some(file)
     };
 
+    let envelope = RecipeEnvelope::new(
+        &batch_config.bot_config.bot_types[genetic_index],
+        &genetic_config.game,
+        &game_info,
+        serde_json::Value::Null,
+    );
     let bot_stack: Arc<Mutex<BotStack>> = Arc::new(Mutex::new(BotStack::new(
         genetic_config.num_samples,
         scores_file,
+        envelope,
     )));
     bot_stack
         .lock()
         .unwrap()
         .push(BotSample::new(bots[genetic_index].to_json()));
 
+    if genetic_config.status_port > 0 {
+        start_status_server(genetic_config.status_port, bot_stack.clone())?;
+    }
+
     let mut pool = Vec::new();
 
-    for _ in 0..6 {
+    for _ in 0..genetic_config.num_threads {
         let mut factory = bot_factory.clone();
         let thread_batch_config = batch_config.clone();
         let thread_bot_stack = bot_stack.clone();
+        let thread_game_factory = game_factory.clone();
 
-        let handle = thread::spawn(move || {
+        let handle = thread::spawn(move || -> NaughtsResult<()> {
             let mut last_result = BotSample::null();
             loop {
                 let lr = std::mem::replace(&mut last_result, BotSample::null());
                 let recipe = {
-                    match thread_bot_stack
-                        .lock()
-                        .ok()
-                        .map(|mut x| x.next(lr))
-                        .flatten()
-                    {
+                    match thread_bot_stack.lock().ok().and_then(|mut x| x.next(lr)) {
                         Some(x) => x,
                         None => break,
                     }
@@ -253,7 +346,7 @@ pub fn gen2_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
                 }
 
                 // Mutate this bot.
-                let mut bot = factory.create_genetic_bot_with_custom_recipe(&recipe.recipe);
+                let mut bot = factory.create_genetic_bot_with_custom_recipe(&recipe.recipe)?;
                 bot.mutate();
                 let new_recipe = bot.to_json();
 
@@ -261,7 +354,8 @@ pub fn gen2_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
                 let last_gen = recipe.generation;
                 let last_score = recipe.score;
                 factory.set_genetic_recipe(new_recipe.clone());
-                let batch_result = run_batch(&thread_batch_config, false, game_factory, &factory);
+                let batch_result =
+                    run_batch(&thread_batch_config, false, &thread_game_factory, &factory)?;
                 let genetic_score = if genetic_index == 0 {
                     batch_result.get_score1()
                 } else {
@@ -269,16 +363,61 @@ pub fn gen2_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
                 };
                 last_result = BotSample::new_from(last_gen, last_score, new_recipe, genetic_score);
             }
+            Ok(())
         });
 
         pool.push(handle);
     }
 
+    let mut result = Ok(());
     for handle in pool {
-        if let Err(e) = handle.join() {
-            error!("Error joining thread: {:?}", e);
+        match handle.join() {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => result = Err(e),
+            Err(e) => error!("Error joining thread: {:?}", e),
         }
     }
 
-    Ok(())
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::botfactory::BotType;
+    use crate::engine::gamefactory::GameType;
+
+    #[test]
+    fn test_get_status() {
+        let bot_type: BotType = "genbot3".parse().unwrap();
+        let game: GameType = "naughts".parse().unwrap();
+        let envelope = RecipeEnvelope::new(
+            &bot_type,
+            &game,
+            &game.get_game_info(),
+            serde_json::Value::Null,
+        );
+        let mut stack = BotStack::new(10, None, envelope);
+        stack.push(BotSample::null());
+
+        let status = stack.get_status();
+        assert_eq!(status["generation"], 0);
+        assert_eq!(status["stack_depth"], 1);
+        assert_eq!(status["samples"], 0);
+        assert!(status["best_score"].is_null());
+        assert!(status["best_recipe"].is_null());
+
+        // A scored child of the first sample is the new best, and goes on top of the stack.
+        let child = BotSample::new_from(0, None, serde_json::json!("child"), 5.0);
+        assert_eq!(stack.next(child).unwrap().generation, 1);
+        let status = stack.get_status();
+        assert_eq!(status["generation"], 1);
+        assert_eq!(status["max_generation"], 1);
+        assert_eq!(status["stack_depth"], 2);
+        assert_eq!(status["samples"], 1);
+        assert_eq!(status["best_score"], 5.0);
+        assert_eq!(status["best_generation"], 1);
+        assert_eq!(status["best_recipe"]["payload"], "child");
+        assert_eq!(status["best_recipe"]["metadata"]["generation"], 1);
+    }
 }
@@ -3,6 +3,7 @@ use std::sync::mpsc::channel;
 use threadpool::ThreadPool;
 
 use crate::engine::botfactory::BotFactory;
+use crate::engine::errors::NaughtsResult;
 use crate::engine::gamebase::run_batch;
 use crate::engine::gameconfig::BatchConfig;
 use crate::engine::gamefactory::GameFactory;
@@ -17,36 +18,36 @@ pub struct GeneticRecipe {
 
 pub fn process_batch(
     batch_config: &BatchConfig,
-    game_factory: GameFactory,
+    game_factory: &GameFactory,
     bot_factory: &mut BotFactory,
     sample_recipe: serde_json::Value,
     index: u32,
     genetic_index: usize,
-) -> GeneticRecipe {
+) -> NaughtsResult<GeneticRecipe> {
     bot_factory.set_recipe(genetic_index, sample_recipe.clone());
-    let batch_result = run_batch(batch_config, false, game_factory, &bot_factory);
+    let batch_result = run_batch(batch_config, false, game_factory, bot_factory)?;
     let genetic_score = if genetic_index == 0 {
         batch_result.get_score1()
     } else {
         batch_result.get_score2()
     };
 
-    GeneticRecipe {
+    Ok(GeneticRecipe {
         recipe: sample_recipe,
         genetic_score,
         index,
-    }
+    })
 }
 
 pub trait BatchProcessor {
     fn process_batches(
         &self,
-        game_factory: GameFactory,
+        game_factory: &GameFactory,
         bot_factory: &mut BotFactory,
         samples: Vec<serde_json::Value>,
         selected_recipes: &mut Vec<GeneticRecipe>,
         score_threshold: GameScore,
-    );
+    ) -> NaughtsResult<()>;
 }
 
 pub struct MTBatchProcessor {
@@ -68,12 +69,12 @@ impl MTBatchProcessor {
 impl BatchProcessor for MTBatchProcessor {
     fn process_batches(
         &self,
-        game_factory: GameFactory,
+        game_factory: &GameFactory,
         bot_factory: &mut BotFactory,
         samples: Vec<serde_json::Value>,
         selected_recipes: &mut Vec<GeneticRecipe>,
         score_threshold: GameScore,
-    ) {
+    ) -> NaughtsResult<()> {
         let (tx, rx) = channel();
         let mut samples = samples;
         let mut index = 0;
@@ -84,12 +85,13 @@ impl BatchProcessor for MTBatchProcessor {
             let thread_batch_config = self.batch_config.clone();
             let genetic_index = self.genetic_index;
             let mut bot_factory_clone = bot_factory.clone();
+            let thread_game_factory = game_factory.clone();
 
             let tx = tx.clone();
             self.pool.execute(move || {
                 let item = process_batch(
                     &thread_batch_config,
-                    game_factory,
+                    &thread_game_factory,
                     &mut bot_factory_clone,
                     sample,
                     index,
@@ -100,7 +102,16 @@ impl BatchProcessor for MTBatchProcessor {
             index += 1;
         }
 
+        // Wait for every batch, even if one fails, so that no results are sent after returning.
+        let mut result = Ok(());
         for item in rx.iter().take(sample_count) {
+            let item = match item {
+                Ok(x) => x,
+                Err(e) => {
+                    result = Err(e);
+                    continue;
+                }
+            };
             let mut win = String::new();
             let index = item.index;
             let score = item.genetic_score;
@@ -113,6 +124,7 @@ impl BatchProcessor for MTBatchProcessor {
                 index, score, win
             );
         }
+        result
     }
 }
 
@@ -133,12 +145,12 @@ impl STBatchProcessor {
 impl BatchProcessor for STBatchProcessor {
     fn process_batches(
         &self,
-        game_factory: GameFactory,
+        game_factory: &GameFactory,
         bot_factory: &mut BotFactory,
         samples: Vec<serde_json::Value>,
         selected_recipes: &mut Vec<GeneticRecipe>,
         score_threshold: GameScore,
-    ) {
+    ) -> NaughtsResult<()> {
         let mut samples = samples;
         let mut index = 0;
         while !samples.is_empty() {
@@ -153,7 +165,7 @@ impl BatchProcessor for STBatchProcessor {
                 sample,
                 index,
                 self.genetic_index,
-            );
+            )?;
 
             let mut win = String::new();
             let score = item.genetic_score;
@@ -167,5 +179,6 @@ impl BatchProcessor for STBatchProcessor {
             );
             index += 1;
         }
+        Ok(())
     }
 }
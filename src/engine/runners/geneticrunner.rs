@@ -1,14 +1,14 @@
 use log::{error, info, warn};
 use std::cmp;
-use std::env;
-use std::error::Error;
 use std::fs::OpenOptions;
 use std::io::Write;
 
 use crate::engine::botdb::BotDB;
 use crate::engine::botfactory::{BotFactory, BotType};
+use crate::engine::errors::{NaughtsError, NaughtsResult};
 use crate::engine::gameconfig::GameConfig;
 use crate::engine::gamefactory::create_game_factory;
+use crate::engine::recipe::RecipeEnvelope;
 use crate::engine::runners::genetic::processor::{BatchProcessor, GeneticRecipe, MTBatchProcessor};
 
 fn generate_original_samples(count: u32, recipe: &serde_json::Value) -> Vec<serde_json::Value> {
@@ -25,11 +25,11 @@ fn generate_samples(
     bot_factory: &BotFactory,
     input_recipes: &[GeneticRecipe],
     num_samples: u32,
-) -> Vec<serde_json::Value> {
+) -> NaughtsResult<Vec<serde_json::Value>> {
     let mut samples_out = Vec::with_capacity(input_recipes.len() * num_samples as usize);
     for recipe in input_recipes {
         for _ in 0..num_samples {
-            let mut bot = bot_factory.create_bot_with_custom_recipe(bot_type, &recipe.recipe);
+            let mut bot = bot_factory.create_bot_with_custom_recipe(bot_type, &recipe.recipe)?;
             bot.mutate();
             let recipe_out = bot.to_json();
             if recipe_out == recipe.recipe {
@@ -38,7 +38,7 @@ fn generate_samples(
             samples_out.push(recipe_out);
         }
     }
-    samples_out
+    Ok(samples_out)
 }
 
 pub fn filter_samples(selected_recipes: &mut Vec<GeneticRecipe>, keep_samples: usize) {
@@ -52,7 +52,7 @@ pub fn filter_samples(selected_recipes: &mut Vec<GeneticRecipe>, keep_samples: u
     selected_recipes.drain(keep..);
 }
 
-pub fn genetic_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
+pub fn genetic_runner(config: GameConfig) -> NaughtsResult<()> {
     let botdb = config.botdb;
     let botrecipe = &config.botrecipe;
     if !botrecipe.is_null() {
@@ -61,9 +61,9 @@ pub fn genetic_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
     let genetic_config = config.get_genetic_config();
     let batch_config = genetic_config.batch_config;
     let game_factory = create_game_factory(&genetic_config.game);
-    let game = game_factory();
-    let mut bot_factory = BotFactory::new(game.get_game_info(), config.get_bot_config());
-    let (bot1, bot2) = bot_factory.create_bots();
+    let game_info = game_factory().get_game_info();
+    let mut bot_factory = BotFactory::new(game_info.clone(), config.get_bot_config());
+    let (bot1, bot2) = bot_factory.create_bots()?;
     let bots = vec![bot1, bot2];
     let num_samples = genetic_config.num_samples;
 
@@ -77,9 +77,12 @@ pub fn genetic_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
         }
 
         0
-    } else {
-        assert!(bots[1].is_genetic(), "Neither bot is a genetic bot!");
+    } else if bots[1].is_genetic() {
         1
+    } else {
+        return Err(NaughtsError::Config(String::from(
+            "Neither bot is a genetic bot, e.g. genbot3",
+        )));
     };
 
     let genetic_name = &batch_config.bot_config.bot_names[genetic_index].as_str();
@@ -90,25 +93,27 @@ pub fn genetic_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
 
     let mut best_botid = String::new();
 
+    let experiment_path = config.save_experiment_config()?;
+    info!("Saved config to {}", experiment_path.display());
+
     let mut scores_file = {
-        let scores_path = match env::current_exe() {
-            Ok(x) => {
-                let mut p = x.parent().expect("Error getting parent dir").to_path_buf();
-                p.push("scores.csv");
-                p
-            }
-            _ => panic!("Error getting current exe path"),
-        };
-        Some(
-            OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(scores_path)
-                .expect("Error opening scores.csv"),
-        )
+        let mut scores_path = genetic_config.output_dir.clone();
+        scores_path.push("scores.csv");
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&scores_path)
+            .map_err(|e| {
+                NaughtsError::Io(format!("Error opening {}: {}", scores_path.display(), e))
+            })?;
+        Some(file)
     };
 
-    let processor = MTBatchProcessor::new(6, batch_config.clone(), genetic_index);
+    let processor = MTBatchProcessor::new(
+        genetic_config.num_threads as usize,
+        batch_config.clone(),
+        genetic_index,
+    );
 
     for gen in 0..genetic_config.num_generations {
         info!("--------------------------");
@@ -117,7 +122,7 @@ pub fn genetic_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
         let mut new_samples = if selected_recipes.is_empty() {
             generate_original_samples(num_samples, botrecipe)
         } else {
-            generate_samples(genetic_type, &bot_factory, &selected_recipes, num_samples)
+            generate_samples(genetic_type, &bot_factory, &selected_recipes, num_samples)?
         };
 
         if genetic_config.wild_samples > 0 {
@@ -126,12 +131,12 @@ pub fn genetic_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
 
         let recipe_count = selected_recipes.len();
         processor.process_batches(
-            game_factory,
+            &game_factory,
             &mut bot_factory,
             new_samples,
             &mut selected_recipes,
             score_threshold,
-        );
+        )?;
         if selected_recipes.len() == recipe_count {
             info!(
                 "Generation {} :: No improvement - will generate more samples",
@@ -150,12 +155,23 @@ pub fn genetic_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
             if recipe.genetic_score > score_threshold {
                 // Lifting the score more slowly avoids getting stuck due to a random fluke
                 // increasing it out of reach in one jump.
-                score_threshold += (recipe.genetic_score - score_threshold) * 0.2;
+                score_threshold +=
+                    (recipe.genetic_score - score_threshold) * genetic_config.lifting_factor;
 
                 // Write scores somewhere.
+                let saved_recipe = RecipeEnvelope::new(
+                    genetic_type,
+                    &genetic_config.game,
+                    &game_info,
+                    recipe.recipe.clone(),
+                )
+                .with_metadata("score", serde_json::json!(recipe.genetic_score))
+                .with_metadata("generation", serde_json::json!(gen))
+                .to_json();
                 if botdb {
-                    match BotDB::new().save_bot(&genetic_name, &recipe.recipe, recipe.genetic_score)
-                    {
+                    match BotDB::new().and_then(|mut x| {
+                        x.save_bot(genetic_name, &saved_recipe, recipe.genetic_score)
+                    }) {
                         Ok(x) => {
                             info!("BotID {}", x);
                             best_botid = x;
@@ -163,7 +179,7 @@ pub fn genetic_runner(config: GameConfig) -> Result<(), Box<dyn Error>> {
                         Err(x) => error!("Error saving bot: {}", x),
                     };
                 } else if let Some(x) = &mut scores_file {
-                    writeln!(x, "{}", recipe.recipe.to_string()).expect("Error writing scores.csv");
+                    writeln!(x, "{}", saved_recipe)?;
                 }
             }
 
@@ -0,0 +1,40 @@
+use crate::engine::botregistry::get_bots;
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gameconfig::GameConfig;
+use crate::engine::gameregistry::get_games;
+
+/// Print the available bots and their options, optionally only those that can play the --game.
+pub fn list_bots_runner(config: GameConfig) -> NaughtsResult<()> {
+    for spec in get_bots() {
+        if !config.get_game_filter().is_none_or(|x| spec.can_play(x)) {
+            continue;
+        }
+
+        let name = match spec.arg {
+            Some(ref x) => format!("{}:<{}>", spec.name, x),
+            None => spec.name.clone(),
+        };
+        println!("{:<20}{}", name, spec.description);
+        for param in spec.params.iter() {
+            println!(
+                "{:<20}  [{}] {} = {}: {}",
+                "", spec.name, param.name, param.default, param.description
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Print the available games and their options.
+pub fn list_games_runner(_config: GameConfig) -> NaughtsResult<()> {
+    for spec in get_games() {
+        println!("{:<20}{}", spec.name, spec.description);
+        for param in spec.params.iter() {
+            println!(
+                "{:<20}  {} = {}: {}",
+                "", param.name, param.default, param.description
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,284 @@
+use log::info;
+use std::io::{self, BufRead, Write};
+
+use crate::engine::botfactory::{BotFactory, DynBot};
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+use crate::engine::gamebase::{get_nearest_move, load_game, GameTrait};
+use crate::engine::gameconfig::GameConfig;
+use crate::engine::gamefactory::{create_game_factory, GameFactory};
+
+/// A game in progress, plus the bot that answers "go" commands.
+///
+/// Commands are read one per line, and each command gets exactly one line in reply:
+///
+///   newgame            Start a new game with player 0 to move.           -> ok
+///   position <json>    Set the game state. The JSON is either the game
+///                      state itself (player 0 to move), or
+///                      {"state": <game state>, "player": <0 or 1>}.      -> ok
+///                      Games that record the player to move (checkers,
+///                      nim) use their own, and reject a different player.
+///   move <n>           Play move n for the player to move.               -> ok
+///   go                 Choose a move for the player to move.             -> bestmove <n>
+///   quit               Exit.
+///
+/// Any failure is reported as "error <message>".
+pub struct ServeSession {
+    game_factory: GameFactory,
+    game: Box<dyn GameTrait>,
+    player: usize,
+    bot: DynBot,
+}
+
+impl ServeSession {
+    pub fn new(game_factory: GameFactory, bot: DynBot) -> Self {
+        let game = game_factory();
+        ServeSession {
+            game_factory,
+            game,
+            player: 0,
+            bot,
+        }
+    }
+
+    /// Handle one command line. Returns None if the session should end.
+    pub fn handle_command(&mut self, line: &str) -> Option<String> {
+        let line = line.trim();
+        let (command, args) = match line.split_once(' ') {
+            Some((x, y)) => (x, y.trim()),
+            None => (line, ""),
+        };
+
+        let result = match command {
+            "newgame" => {
+                self.game = (self.game_factory)();
+                self.player = 0;
+                Ok(String::from("ok"))
+            }
+            "position" => self.set_position(args),
+            "move" => self.play_move(args),
+            "go" => self.go(),
+            "quit" => return None,
+            _ => Err(format!("Unknown command: {}", command)),
+        };
+
+        Some(result.unwrap_or_else(|x| format!("error {}", x)))
+    }
+
+    fn set_position(&mut self, args: &str) -> Result<String, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(args).map_err(|e| format!("Invalid JSON: {}", e))?;
+        let (state, player) = match value.get("state") {
+            Some(x) => (x, value.get("player").and_then(|x| x.as_u64())),
+            None => (&value, None),
+        };
+        if let Some(x) = player.filter(|x| *x > 1) {
+            return Err(format!("Invalid player: {}", x));
+        }
+
+        let game = load_game(&self.game_factory, state).map_err(|e| e.to_string())?;
+        let player = match (game.get_player_to_move(), player) {
+            (Some(x), Some(y)) if x as u64 != y => {
+                return Err(format!(
+                    "Player {} is to move in this position, not {}",
+                    x, y
+                ))
+            }
+            (Some(x), _) => x,
+            (None, y) => y.unwrap_or(0) as usize,
+        };
+        self.game = game;
+        self.player = player;
+        Ok(String::from("ok"))
+    }
+
+    /// Get the current game.
+    pub fn get_game(&self) -> &dyn GameTrait {
+        self.game.as_ref()
+    }
+
+    /// Get the index of the player to move.
+    pub fn get_player(&self) -> usize {
+        self.player
+    }
+
+    fn play_move(&mut self, args: &str) -> Result<String, String> {
+        let output: u32 = args
+            .parse()
+            .map_err(|_| format!("Invalid move: {}", args))?;
+        self.apply_move(output).map_err(|e| e.to_string())?;
+        Ok(String::from("ok"))
+    }
+
+    fn go(&mut self) -> Result<String, String> {
+        let output = self.get_bot_move().map_err(|e| e.to_string())?;
+        Ok(format!("bestmove {}", output))
+    }
+
+    /// Play the specified move for the player to move.
+    pub fn apply_move(&mut self, output: u32) -> NaughtsResult<()> {
+        if self.game.is_ended() {
+            return Err(NaughtsError::GameRule(String::from("Game is over")));
+        }
+
+        let (_, available_moves) = self.game.get_inputs(self.player);
+        if !available_moves.contains(&output) {
+            return Err(NaughtsError::GameRule(format!(
+                "Illegal move {}, expected one of {:?}",
+                output, available_moves
+            )));
+        }
+
+        self.game.update(self.player, output);
+        self.player = self.game.get_next_index(self.player);
+        Ok(())
+    }
+
+    /// Ask the bot to choose a move for the player to move, without playing it.
+    pub fn get_bot_move(&mut self) -> NaughtsResult<u32> {
+        if self.game.is_ended() {
+            return Err(NaughtsError::GameRule(String::from("Game is over")));
+        }
+
+        let identities = self.game.get_identities();
+        self.bot
+            .setup(identities[self.player], identities[1 - self.player]);
+        let (inputs, available_moves) = self.game.get_inputs(self.player);
+        let output = self.bot.process(inputs, &available_moves);
+        if available_moves.contains(&output) {
+            Ok(output)
+        } else {
+            Ok(get_nearest_move(&available_moves, output))
+        }
+    }
+}
+
+pub fn serve_runner(config: GameConfig) -> NaughtsResult<()> {
+    let game_factory = create_game_factory(&config.game);
+    let game = game_factory();
+    let bot_factory = BotFactory::new(game.get_game_info(), config.get_bot_config());
+    let mut session = ServeSession::new(game_factory, bot_factory.create_bot_at(0)?);
+    info!("Serving moves for {}", bot_factory.bot_names().0);
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match session.handle_command(&line) {
+            Some(x) => {
+                let mut out = stdout.lock();
+                writeln!(out, "{}", x)?;
+                out.flush()?;
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::randombot::rbot::RandomBot;
+    use crate::engine::gamefactory::GameType;
+    use crate::games::naughts::singlegame::NaughtsGame;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_commands() {
+        let game_factory: GameFactory = Arc::new(|| Box::new(NaughtsGame::new()));
+        let bot = Box::new(RandomBot::new(&game_factory().get_game_info()));
+        let mut session = ServeSession::new(game_factory, bot);
+
+        assert_eq!(session.handle_command("move 4").unwrap(), "ok");
+        assert!(session
+            .handle_command("move 4")
+            .unwrap()
+            .starts_with("error"));
+        let reply = session.handle_command("go").unwrap();
+        let output: u32 = reply.trim_start_matches("bestmove ").parse().unwrap();
+        assert_ne!(output, 4);
+
+        assert_eq!(session.handle_command("newgame").unwrap(), "ok");
+        assert!(session
+            .handle_command("go")
+            .unwrap()
+            .starts_with("bestmove"));
+        assert!(session
+            .handle_command("bogus")
+            .unwrap()
+            .starts_with("error"));
+        assert_eq!(session.handle_command("quit"), None);
+    }
+
+    #[test]
+    fn test_invalid_positions() {
+        let game_type: GameType = "hex".parse().unwrap();
+        let game_factory = create_game_factory(&game_type);
+        let bot = Box::new(RandomBot::new(&game_factory().get_game_info()));
+        let mut session = ServeSession::new(game_factory, bot);
+
+        let invalid = [
+            r#"position {"board":{"data":""}}"#,
+            r#"position {"board":{"data":"XO"}}"#,
+            r#"position {"board":{"data":"?        "}}"#,
+            // A valid 3x3 board, but this game is 7x7.
+            r#"position {"board":{"data":"X        "}}"#,
+            r#"position {"state":{"board":{"data":"X"}},"player":1}"#,
+            "position not json",
+        ];
+        for command in invalid.iter() {
+            let reply = session.handle_command(command).unwrap();
+            assert!(reply.starts_with("error"), "{} gave {}", command, reply);
+        }
+        assert!(session
+            .handle_command("go")
+            .unwrap()
+            .starts_with("bestmove"));
+
+        let mut session = ServeSession::new(
+            Arc::new(|| Box::new(NaughtsGame::new())),
+            Box::new(RandomBot::new(&NaughtsGame::new().get_game_info())),
+        );
+        let reply = session.handle_command(r#"position {"data":"X"}"#).unwrap();
+        assert!(reply.starts_with("error"), "{}", reply);
+        assert_eq!(
+            session.handle_command(r#"position {"data":"XO-------"}"#),
+            Some(String::from("ok"))
+        );
+        assert_eq!(session.get_game().to_json()["data"], "XO-------");
+    }
+
+    #[test]
+    fn test_position_player_to_move() {
+        let game_type: GameType = "checkers".parse().unwrap();
+        let game_factory = create_game_factory(&game_type);
+        let bot = Box::new(RandomBot::new(&game_factory().get_game_info()));
+        let mut session = ServeSession::new(game_factory, bot);
+
+        // Play X's first move, so that O is to move.
+        let (_, moves) = session.get_game().get_inputs(0);
+        session.apply_move(moves[0]).unwrap();
+        let state = session.get_game().to_json();
+        assert_eq!(state["next_index"], 1);
+
+        // Without a player, the side to move comes from the state.
+        session.handle_command("newgame").unwrap();
+        let reply = session.handle_command(&format!("position {}", state));
+        assert_eq!(reply, Some(String::from("ok")));
+        assert_eq!(session.get_player(), 1);
+        let reply = session.handle_command("go").unwrap();
+        let output: u32 = reply.trim_start_matches("bestmove ").parse().unwrap();
+        assert!(session.get_game().get_inputs(1).1.contains(&output));
+
+        let command = format!("position {}", json!({ "state": state, "player": 0 }));
+        let reply = session.handle_command(&command).unwrap();
+        assert!(reply.starts_with("error"), "{}", reply);
+        let command = format!("position {}", json!({ "state": state, "player": 1 }));
+        assert_eq!(session.handle_command(&command), Some(String::from("ok")));
+    }
+}
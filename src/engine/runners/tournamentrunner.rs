@@ -0,0 +1,87 @@
+use log::info;
+use std::str::FromStr;
+
+use crate::engine::botfactory::{BotFactory, BotType};
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+use crate::engine::gamebase::run_one_game;
+use crate::engine::gameconfig::GameConfig;
+use crate::engine::gamefactory::create_game_factory;
+use crate::engine::gameresult::GameScore;
+
+/// Tournament results for one bot.
+#[derive(Debug, Default)]
+struct Standing {
+    name: String,
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    total_score: GameScore,
+}
+
+impl Standing {
+    fn add(&mut self, score: GameScore, opponent_score: GameScore) {
+        self.total_score += score;
+        if score > opponent_score {
+            self.wins += 1;
+        } else if score < opponent_score {
+            self.losses += 1;
+        } else {
+            self.draws += 1;
+        }
+    }
+}
+
+/// Play a round robin between all tournament bots. Every bot plays a batch against every other
+/// bot, once as the first player and once as the second player.
+pub fn tournament_runner(config: GameConfig) -> NaughtsResult<()> {
+    let game_factory = create_game_factory(&config.game);
+    let game_info = game_factory().get_game_info();
+    let mut batch_config = config.get_batch_config();
+
+    let bots = config.get_tournament_bots();
+    let mut standings: Vec<Standing> = bots
+        .iter()
+        .map(|x| Standing {
+            name: x.clone(),
+            ..Default::default()
+        })
+        .collect();
+
+    for first in 0..bots.len() {
+        for second in 0..bots.len() {
+            if first == second {
+                continue;
+            }
+
+            let bot_config = &mut batch_config.bot_config;
+            bot_config.bot_names = [bots[first].clone(), bots[second].clone()];
+            bot_config.bot_types = [
+                BotType::from_str(&bots[first]).map_err(NaughtsError::Config)?,
+                BotType::from_str(&bots[second]).map_err(NaughtsError::Config)?,
+            ];
+            let bot_factory = BotFactory::new(game_info.clone(), bot_config.clone());
+
+            for _ in 0..batch_config.batch_size {
+                let result = run_one_game(&batch_config, false, &game_factory, &bot_factory)?;
+                standings[first].add(result.get_score1(), result.get_score2());
+                standings[second].add(result.get_score2(), result.get_score1());
+            }
+            info!("Played {} vs {}", bots[first], bots[second]);
+        }
+    }
+
+    standings.sort_by(|a, b| b.total_score.partial_cmp(&a.total_score).unwrap());
+
+    info!("");
+    info!(
+        "{:<20} {:>6} {:>6} {:>6} {:>10}",
+        "BOT", "WINS", "DRAWS", "LOSSES", "SCORE"
+    );
+    for standing in &standings {
+        info!(
+            "{:<20} {:>6} {:>6} {:>6} {:>10.3}",
+            standing.name, standing.wins, standing.draws, standing.losses, standing.total_score
+        );
+    }
+    Ok(())
+}
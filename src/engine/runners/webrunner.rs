@@ -0,0 +1,447 @@
+use log::{info, warn};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::engine::botfactory::{BotFactory, BotType};
+use crate::engine::botregistry::get_bots;
+use crate::engine::errors::{NaughtsError, NaughtsResult};
+use crate::engine::gameconfig::{load_recipe_file, GameConfig};
+use crate::engine::gamefactory::{create_game_factory, GameFactory};
+use crate::engine::recipe::RecipeEnvelope;
+use crate::engine::runners::serverunner::ServeSession;
+
+const INDEX_HTML: &str = include_str!("webui.html");
+
+/// Most unfinished games to keep. The browser never deletes a game it abandons, so the oldest
+/// games are dropped to make room for new ones.
+const MAX_GAMES: usize = 100;
+
+/// One game being played in the browser.
+struct WebGame {
+    session: ServeSession,
+    bot_name: String,
+    human_player: usize,
+    last_move: Option<u32>,
+}
+
+impl WebGame {
+    fn to_json(&self, id: u64) -> serde_json::Value {
+        let game = self.session.get_game();
+        let player = self.session.get_player();
+        let ended = game.is_ended();
+        let available_moves = if ended {
+            Vec::new()
+        } else {
+            game.get_inputs(player).1
+        };
+        let identities: Vec<String> = game
+            .get_identities()
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        let result = if ended {
+            let result = game.get_result();
+            serde_json::json!({
+                "winner": result.get_winner_identity().map(|x| x.to_string()),
+                "scores": [result.get_score1(), result.get_score2()],
+                "text": result.to_string()
+            })
+        } else {
+            serde_json::Value::Null
+        };
+
+        serde_json::json!({
+            "id": id,
+            "bot": self.bot_name,
+            "human_player": self.human_player,
+            "player": player,
+            "identities": identities,
+            "state": game.to_json(),
+            "available_moves": available_moves,
+            "ended": ended,
+            "result": result,
+            "last_move": self.last_move
+        })
+    }
+}
+
+/// State shared by all requests. Requests are handled one at a time.
+struct WebState {
+    config: GameConfig,
+    game_factory: GameFactory,
+    bot_factory: BotFactory,
+    games: HashMap<u64, WebGame>,
+    next_id: u64,
+}
+
+type ApiResult = Result<serde_json::Value, (u16, String)>;
+
+fn bad_request<S: ToString>(message: S) -> (u16, String) {
+    (400, message.to_string())
+}
+
+fn not_found<S: ToString>(message: S) -> (u16, String) {
+    (404, message.to_string())
+}
+
+/// Missing files are reported as not found, and everything else as a bad request.
+fn error_response(error: NaughtsError) -> (u16, String) {
+    match error {
+        NaughtsError::Io(_) => not_found(error),
+        _ => bad_request(error),
+    }
+}
+
+impl WebState {
+    fn new(config: GameConfig) -> Self {
+        let game_factory = create_game_factory(&config.game);
+        let bot_factory = BotFactory::new(game_factory().get_game_info(), config.get_bot_config());
+        WebState {
+            config,
+            game_factory,
+            bot_factory,
+            games: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    fn get_recipe_dir(&self) -> PathBuf {
+        let mut dir = self.config.get_data_path().to_path_buf();
+        dir.push("recipes");
+        dir
+    }
+
+    fn list_recipes(&self) -> Vec<String> {
+        let mut recipes: Vec<String> = match fs::read_dir(self.get_recipe_dir()) {
+            Ok(entries) => entries
+                .filter_map(|x| x.ok())
+                .filter(|x| x.path().is_file())
+                .filter_map(|x| x.file_name().into_string().ok())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        recipes.sort();
+        recipes
+    }
+
+    /// Get the names of the bots that can be chosen in the web UI for this game.
+    fn list_bots(&self) -> Vec<String> {
+        get_bots()
+            .into_iter()
+            .filter(|x| x.web && x.can_play(&self.config.game))
+            .map(|x| x.name)
+            .collect()
+    }
+
+    fn get_info(&self) -> serde_json::Value {
+        let game = (self.game_factory)();
+        let info = game.get_game_info();
+        let identities: Vec<String> = game
+            .get_identities()
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        serde_json::json!({
+            "game": self.config.game.to_string(),
+            "description": self.config.game.get_spec().description,
+            "identities": identities,
+            "game_info": {
+                "input_count": info.input_count,
+                "output_count": info.output_count
+            },
+            "default_bot": self.config.get_bot_config().bot_names[0]
+        })
+    }
+
+    fn create_game(&mut self, body: &serde_json::Value) -> ApiResult {
+        let bot_name = body
+            .get("bot")
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_ascii_lowercase())
+            .unwrap_or_else(|| self.config.get_bot_config().bot_names[0].clone());
+        if !self.list_bots().contains(&bot_name) {
+            return Err(bad_request(format!("Unknown bot: {}", bot_name)));
+        }
+        let bot_type = BotType::from_str(&bot_name).map_err(bad_request)?;
+
+        let human_player = body
+            .get("human_player")
+            .and_then(|x| x.as_u64())
+            .unwrap_or(0);
+        if human_player > 1 {
+            return Err(bad_request(format!("Invalid player: {}", human_player)));
+        }
+
+        let bot = match body.get("recipe").and_then(|x| x.as_str()) {
+            Some(x) if !x.is_empty() => {
+                // Only allow files directly inside the recipe dir.
+                if Path::new(x).file_name().and_then(|f| f.to_str()) != Some(x) {
+                    return Err(bad_request(format!("Invalid recipe name: {}", x)));
+                }
+                let mut path = self.get_recipe_dir();
+                path.push(x);
+                let envelope = load_recipe_file(&path)
+                    .and_then(|x| RecipeEnvelope::from_json(&x))
+                    .map_err(error_response)?;
+                let game_info = (self.game_factory)().get_game_info();
+                envelope
+                    .check(&bot_type, &self.config.game, &game_info)
+                    .map_err(error_response)?;
+                self.bot_factory
+                    .create_bot_with_custom_recipe(&bot_type, &envelope.payload)
+                    .map_err(error_response)?
+            }
+            _ => self.bot_factory.create_bot(&bot_type),
+        };
+
+        while self.games.len() >= MAX_GAMES {
+            let oldest = *self.games.keys().min().expect("Games should not be empty");
+            self.games.remove(&oldest);
+            info!("Dropped web game {}", oldest);
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let game = WebGame {
+            session: ServeSession::new(self.game_factory.clone(), bot),
+            bot_name,
+            human_player: human_player as usize,
+            last_move: None,
+        };
+        let value = game.to_json(id);
+        self.games.insert(id, game);
+        info!("Created web game {}", id);
+        Ok(value)
+    }
+
+    fn get_game(&mut self, id: u64) -> Result<&mut WebGame, (u16, String)> {
+        self.games
+            .get_mut(&id)
+            .ok_or_else(|| not_found(format!("Game not found: {}", id)))
+    }
+
+    /// Get the state of a game after a move. A game that has ended is removed, since the
+    /// state already includes the result and there are no more moves to make.
+    fn move_reply(&mut self, id: u64) -> ApiResult {
+        let game = self.get_game(id)?;
+        let value = game.to_json(id);
+        if game.session.get_game().is_ended() {
+            self.games.remove(&id);
+            info!("Finished web game {}", id);
+        }
+        Ok(value)
+    }
+
+    fn play_move(&mut self, id: u64, body: &serde_json::Value) -> ApiResult {
+        let output = body
+            .get("move")
+            .and_then(|x| x.as_u64())
+            .ok_or_else(|| bad_request("Missing move"))?;
+        let output =
+            u32::try_from(output).map_err(|_| bad_request(format!("Invalid move: {}", output)))?;
+        let game = self.get_game(id)?;
+        if game.session.get_player() != game.human_player {
+            return Err(bad_request("It is not your turn"));
+        }
+        game.session.apply_move(output).map_err(bad_request)?;
+        game.last_move = Some(output);
+        self.move_reply(id)
+    }
+
+    fn play_bot_move(&mut self, id: u64) -> ApiResult {
+        let game = self.get_game(id)?;
+        if game.session.get_player() == game.human_player {
+            return Err(bad_request("It is not the bot's turn"));
+        }
+        let output = game.session.get_bot_move().map_err(bad_request)?;
+        game.session.apply_move(output).map_err(bad_request)?;
+        game.last_move = Some(output);
+        self.move_reply(id)
+    }
+
+    fn handle_api(&mut self, method: &Method, path: &str, body: &serde_json::Value) -> ApiResult {
+        let parts: Vec<&str> = path.split('/').filter(|x| !x.is_empty()).collect();
+        match (method, parts.as_slice()) {
+            (Method::Get, ["info"]) => Ok(self.get_info()),
+            (Method::Get, ["bots"]) => Ok(serde_json::json!(self.list_bots())),
+            (Method::Get, ["recipes"]) => Ok(serde_json::json!(self.list_recipes())),
+            (Method::Post, ["games"]) => self.create_game(body),
+            (_, ["games", id, rest @ ..]) => {
+                let id: u64 = id
+                    .parse()
+                    .map_err(|_| not_found(format!("Game not found: {}", id)))?;
+                match (method, rest) {
+                    (Method::Get, []) => Ok(self.get_game(id)?.to_json(id)),
+                    (Method::Post, ["move"]) => self.play_move(id, body),
+                    (Method::Post, ["botmove"]) => self.play_bot_move(id),
+                    (Method::Delete, []) => {
+                        self.games.remove(&id);
+                        Ok(serde_json::json!({ "id": id }))
+                    }
+                    _ => Err(not_found(format!("Not found: {}", path))),
+                }
+            }
+            _ => Err(not_found(format!("Not found: {}", path))),
+        }
+    }
+
+    fn handle_request(&mut self, mut request: Request) -> std::io::Result<()> {
+        let url = request.url().to_string();
+        let path = url.split('?').next().unwrap_or("").to_string();
+        let method = request.method().clone();
+
+        if method == Method::Get && (path == "/" || path == "/index.html") {
+            let header = Header::from_bytes(&b"Content-Type"[..], &b"text/html; charset=utf-8"[..])
+                .expect("Invalid header");
+            return request.respond(Response::from_string(INDEX_HTML).with_header(header));
+        }
+
+        let result = match path.strip_prefix("/api") {
+            Some(api_path) => {
+                let mut content = String::new();
+                request.as_reader().read_to_string(&mut content)?;
+                if content.trim().is_empty() {
+                    content = String::from("null");
+                }
+                match serde_json::from_str::<serde_json::Value>(&content) {
+                    Ok(body) => self.handle_api(&method, api_path, &body),
+                    Err(e) => Err(bad_request(format!("Invalid JSON: {}", e))),
+                }
+            }
+            None => Err(not_found(format!("Not found: {}", path))),
+        };
+
+        let (status, value) = match result {
+            Ok(x) => (200, x),
+            Err((status, message)) => {
+                warn!("{} {}: {}", method, url, message);
+                (status, serde_json::json!({ "error": message }))
+            }
+        };
+        let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+            .expect("Invalid header");
+        request.respond(
+            Response::from_string(value.to_string())
+                .with_status_code(status)
+                .with_header(header),
+        )
+    }
+}
+
+pub fn web_runner(config: GameConfig) -> NaughtsResult<()> {
+    // Only listen locally. There is no authentication.
+    let address = format!("127.0.0.1:{}", config.web_port);
+    let server = Server::http(&address)
+        .map_err(|e| NaughtsError::Io(format!("Error starting server: {}", e)))?;
+    info!("Web UI running at http://{}/", address);
+
+    let mut state = WebState::new(config);
+    for request in server.incoming_requests() {
+        if let Err(x) = state.handle_request(request) {
+            warn!("Error handling request: {}", x);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(state: &mut WebState, path: &str, body: serde_json::Value) -> ApiResult {
+        state.handle_api(&Method::Post, path, &body)
+    }
+
+    #[test]
+    fn test_create_game() {
+        let mut state = WebState::new(GameConfig::new(std::env::temp_dir()));
+        let game = post(
+            &mut state,
+            "/games",
+            serde_json::json!({ "bot": "randombot" }),
+        )
+        .unwrap();
+        assert_eq!(game["id"], 1);
+        assert_eq!(game["human_player"], 0);
+        assert_eq!(game["available_moves"].as_array().unwrap().len(), 9);
+
+        let body = serde_json::json!({ "bot": "external:ls" });
+        assert_eq!(post(&mut state, "/games", body).unwrap_err().0, 400);
+        let body = serde_json::json!({ "bot": "randombot", "human_player": 2 });
+        assert_eq!(post(&mut state, "/games", body).unwrap_err().0, 400);
+        let body = serde_json::json!({ "bot": "genbot3", "recipe": "../secret" });
+        assert_eq!(post(&mut state, "/games", body).unwrap_err().0, 400);
+
+        let game = state.handle_api(&Method::Get, "/games/1", &serde_json::Value::Null);
+        assert_eq!(game.unwrap()["id"], 1);
+        let game = state.handle_api(&Method::Get, "/games/2", &serde_json::Value::Null);
+        assert_eq!(game.unwrap_err().0, 404);
+    }
+
+    #[test]
+    fn test_list_bots() {
+        let mut state = WebState::new(GameConfig::new(std::env::temp_dir()));
+        let bots = state.handle_api(&Method::Get, "/bots", &serde_json::Value::Null);
+        assert_eq!(
+            bots.unwrap(),
+            serde_json::json!(["randombot", "genbot3", "nbot1", "omnibot"])
+        );
+        let body = serde_json::json!({ "bot": "naughts.human" });
+        assert_eq!(post(&mut state, "/games", body).unwrap_err().0, 400);
+    }
+
+    #[test]
+    fn test_moves() {
+        let mut state = WebState::new(GameConfig::new(std::env::temp_dir()));
+        let body = serde_json::json!({ "bot": "randombot", "human_player": 1 });
+        post(&mut state, "/games", body).unwrap();
+
+        let body = serde_json::json!({ "move": 4 });
+        let error = post(&mut state, "/games/1/move", body).unwrap_err();
+        assert_eq!(error.1, "It is not your turn");
+        let game = post(&mut state, "/games/1/botmove", serde_json::Value::Null).unwrap();
+        assert_eq!(game["player"], 1);
+        let error = post(&mut state, "/games/1/botmove", serde_json::Value::Null).unwrap_err();
+        assert_eq!(error.1, "It is not the bot's turn");
+
+        let bot_move = game["last_move"].as_u64().unwrap();
+        let body = serde_json::json!({ "move": bot_move });
+        assert_eq!(post(&mut state, "/games/1/move", body).unwrap_err().0, 400);
+        let body = serde_json::json!({ "move": u64::from(u32::MAX) + 4 });
+        let error = post(&mut state, "/games/1/move", body).unwrap_err();
+        assert!(error.1.starts_with("Invalid move"), "{}", error.1);
+
+        // Play the game out. The game is removed once it has ended.
+        let mut game = game;
+        while game["ended"] == false {
+            game = if game["player"] == 1 {
+                let body = serde_json::json!({ "move": game["available_moves"][0] });
+                post(&mut state, "/games/1/move", body).unwrap()
+            } else {
+                post(&mut state, "/games/1/botmove", serde_json::Value::Null).unwrap()
+            };
+        }
+        assert!(game["result"]["text"].is_string());
+        let error = post(&mut state, "/games/1/botmove", serde_json::Value::Null).unwrap_err();
+        assert_eq!(error.0, 404);
+    }
+
+    #[test]
+    fn test_max_games() {
+        let mut state = WebState::new(GameConfig::new(std::env::temp_dir()));
+        for _ in 0..MAX_GAMES + 1 {
+            post(
+                &mut state,
+                "/games",
+                serde_json::json!({ "bot": "randombot" }),
+            )
+            .unwrap();
+        }
+        assert_eq!(state.games.len(), MAX_GAMES);
+        assert!(!state.games.contains_key(&1), "The oldest game was dropped");
+    }
+}
@@ -0,0 +1,267 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gameobject::{invalid_state, require_state_field, GameObject};
+
+use log::info;
+use serde_json;
+
+/// Row/col offsets for each direction: up-left, up-right, down-left, down-right.
+const DIRECTIONS: [(isize, isize); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+
+/// The outcome of applying one move (a single step or a single jump).
+pub struct MoveResult {
+    pub landing: usize,
+    pub captured: bool,
+    pub kinged: bool,
+}
+
+/// An 8x8 draughts board.
+///
+/// Only the 32 dark squares are stored, numbered 0-31 left to right, top to bottom, 4 per row.
+/// Row 0 is at the top. Dark squares are those where (row + col) is odd.
+/// X men start on rows 0-2 and move down, O men start on rows 5-7 and move up.
+/// Men are lowercase ('x', 'o'), kings are uppercase ('X', 'O').
+#[derive(Clone)]
+pub struct Board {
+    data: Vec<char>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        let mut data = vec![' '; 32];
+        for (square, piece) in data.iter_mut().enumerate() {
+            if square < 12 {
+                *piece = 'x';
+            } else if square >= 20 {
+                *piece = 'o';
+            }
+        }
+        Board { data }
+    }
+}
+
+impl Board {
+    /// Create a new Board in the starting position.
+    pub fn new() -> Self {
+        Board::default()
+    }
+
+    /// Create an empty Board.
+    pub fn empty() -> Self {
+        Board {
+            data: vec![' '; 32],
+        }
+    }
+
+    /// Get the piece at the specified square.
+    pub fn getat(&self, square: usize) -> char {
+        self.data[square]
+    }
+
+    /// Set the piece at the specified square.
+    pub fn setat(&mut self, square: usize, piece: char) {
+        self.data[square] = piece;
+    }
+
+    /// Get the identity of the player owning the specified piece.
+    pub fn owner(piece: char) -> Option<char> {
+        match piece {
+            'x' | 'X' => Some('X'),
+            'o' | 'O' => Some('O'),
+            _ => None,
+        }
+    }
+
+    fn to_row_col(square: usize) -> (isize, isize) {
+        let row = square / 4;
+        let col = (square % 4) * 2 + (row + 1) % 2;
+        (row as isize, col as isize)
+    }
+
+    fn from_row_col(row: isize, col: isize) -> Option<usize> {
+        if !(0..8).contains(&row) || !(0..8).contains(&col) || (row + col) % 2 == 0 {
+            return None;
+        }
+        Some((row * 4 + col / 2) as usize)
+    }
+
+    /// Get the square `distance` steps away in the specified direction, if it is on the board.
+    fn neighbour(square: usize, direction: usize, distance: isize) -> Option<usize> {
+        let (row, col) = Board::to_row_col(square);
+        let (dr, dc) = DIRECTIONS[direction];
+        Board::from_row_col(row + dr * distance, col + dc * distance)
+    }
+
+    fn get_directions(piece: char) -> &'static [usize] {
+        match piece {
+            'x' => &[2, 3],
+            'o' => &[0, 1],
+            _ => &[0, 1, 2, 3],
+        }
+    }
+
+    /// Get all jumps for the piece on the specified square, as (square * 4 + direction).
+    pub fn get_jumps(&self, square: usize) -> Vec<u32> {
+        let piece = self.data[square];
+        let identity = match Board::owner(piece) {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+
+        let mut moves = Vec::new();
+        for direction in Board::get_directions(piece) {
+            let over = Board::neighbour(square, *direction, 1);
+            let target = Board::neighbour(square, *direction, 2);
+            if let (Some(over), Some(target)) = (over, target) {
+                let over_owner = Board::owner(self.data[over]);
+                if over_owner.is_some() && over_owner != Some(identity) && self.data[target] == ' '
+                {
+                    moves.push((square * 4 + direction) as u32);
+                }
+            }
+        }
+        moves
+    }
+
+    /// Get all non-capturing steps for the piece on the specified square.
+    fn get_steps(&self, square: usize) -> Vec<u32> {
+        let piece = self.data[square];
+        if Board::owner(piece).is_none() {
+            return Vec::new();
+        }
+
+        Board::get_directions(piece)
+            .iter()
+            .filter(|d| Board::neighbour(square, **d, 1).is_some_and(|x| self.data[x] == ' '))
+            .map(|d| (square * 4 + d) as u32)
+            .collect()
+    }
+
+    /// Get all legal moves for the specified player.
+    /// Captures are compulsory, and a multi-jump must continue with the same piece.
+    pub fn get_possible_moves(&self, identity: char, jumping_piece: Option<usize>) -> Vec<u32> {
+        if let Some(square) = jumping_piece {
+            return self.get_jumps(square);
+        }
+
+        let squares: Vec<usize> = (0..32)
+            .filter(|x| Board::owner(self.data[*x]) == Some(identity))
+            .collect();
+        let jumps: Vec<u32> = squares.iter().flat_map(|x| self.get_jumps(*x)).collect();
+        if !jumps.is_empty() {
+            return jumps;
+        }
+        squares.iter().flat_map(|x| self.get_steps(*x)).collect()
+    }
+
+    /// Apply a single step or jump. The move must be legal.
+    pub fn apply_move(&mut self, m: u32) -> MoveResult {
+        let (square, direction) = (m as usize / 4, m as usize % 4);
+        let piece = self.data[square];
+        let captured = self.get_jumps(square).contains(&m);
+        let distance = if captured { 2 } else { 1 };
+        let landing = Board::neighbour(square, direction, distance).expect("Invalid move");
+        if captured {
+            let over = Board::neighbour(square, direction, 1).expect("Invalid move");
+            self.data[over] = ' ';
+        }
+
+        self.data[square] = ' ';
+        let (row, _) = Board::to_row_col(landing);
+        let kinged = (piece == 'x' && row == 7) || (piece == 'o' && row == 0);
+        self.data[landing] = if kinged {
+            piece.to_ascii_uppercase()
+        } else {
+            piece
+        };
+
+        MoveResult {
+            landing,
+            captured,
+            kinged,
+        }
+    }
+
+    /// Display this board on the screen, with square numbers alongside.
+    pub fn show(&self, indent: u8) {
+        let prefix = format!("{:1$}", " ", indent as usize);
+        for row in 0..8 {
+            let mut line = String::new();
+            let mut numbers = String::new();
+            for col in 0..8 {
+                match Board::from_row_col(row, col) {
+                    Some(square) => {
+                        line.push_str(&format!(" {} ", self.data[square]));
+                        numbers.push_str(&format!("{:3}", square));
+                    }
+                    None => {
+                        line.push_str("   ");
+                        numbers.push_str("   ");
+                    }
+                }
+            }
+            info!("{}|{}|   {}", prefix, line, numbers);
+        }
+        info!("");
+    }
+}
+
+/// GameObject lets us serialise and deserialise the contents as JSON.
+impl GameObject for Board {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+          "data": self.data.iter().collect::<String>()
+        })
+    }
+
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let data: String = require_state_field(value, "checkers board", "data")?;
+        if data.chars().count() != 32
+            || !data.chars().all(|c| c == ' ' || Board::owner(c).is_some())
+        {
+            return Err(invalid_state(
+                "checkers board",
+                format!(
+                    "expected 32 squares of ' ', 'x', 'o', 'X' or 'O', got {:?}",
+                    data
+                ),
+            ));
+        }
+        self.data = data.chars().collect();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_forced_multi_jump() {
+        let mut b = Board::empty();
+        b.setat(1, 'x');
+        b.setat(5, 'o');
+        b.setat(13, 'o');
+        b.setat(31, 'o');
+        // Stepping is not allowed while a capture is available.
+        assert_eq!(b.get_possible_moves('X', None), vec![6]);
+
+        let result = b.apply_move(6);
+        assert!(result.captured);
+        assert_eq!(result.landing, 8);
+        assert_eq!(b.getat(5), ' ');
+        assert_eq!(b.get_possible_moves('X', Some(8)), vec![8 * 4 + 3]);
+
+        let result = b.apply_move(8 * 4 + 3);
+        assert_eq!(result.landing, 17);
+        assert!(!result.kinged);
+    }
+
+    #[test]
+    fn test_kinging() {
+        let mut b = Board::empty();
+        b.setat(4, 'o');
+        let result = b.apply_move(4 * 4 + 1);
+        assert!(result.kinged);
+        assert_eq!(b.getat(result.landing), 'O');
+        assert_eq!(b.get_possible_moves('O', None).len(), 2);
+    }
+}
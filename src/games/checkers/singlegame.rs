@@ -0,0 +1,276 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gamebase::{GameInfo, GameTrait};
+use crate::engine::gameobject::{get_state_field, invalid_state, require_state_part, GameObject};
+use crate::engine::gameresult::{GameResult, GameScore};
+use crate::games::checkers::board::Board;
+
+use serde_json;
+
+/// Number of turns in a row without a capture before the game is drawn.
+pub const DEFAULT_DRAW_LIMIT: u32 = 80;
+
+/// English draughts (checkers).
+///
+/// Inputs are 4 lots of 32 squares: this player's men, this player's kings, the opponent's men
+/// and the opponent's kings.
+///
+/// Move encoding:
+/// Each output is a single step or jump, encoded as `square * 4 + direction`, giving 128 outputs.
+/// Squares are numbered 0-31 as described in `Board`. Directions are 0 = up-left, 1 = up-right,
+/// 2 = down-left, 3 = down-right (up being towards row 0, where X starts).
+/// Whether the move is a step or a jump is implied by the board: when a capture is available,
+/// only jumps are offered.
+///
+/// A multi-jump is played as a sequence of single jumps. After a jump, if the same piece can
+/// capture again, the same player moves again and only jumps by that piece are available.
+/// A man that reaches the far row is crowned and its turn ends.
+#[derive(Clone)]
+pub struct CheckersGame {
+    board: Board,
+    jumping_piece: Option<usize>,
+    next_index: usize,
+    turns_without_capture: u32,
+    captured_this_turn: bool,
+    draw_limit: u32,
+    num_turns: [u32; 2],
+}
+
+impl Default for CheckersGame {
+    fn default() -> Self {
+        CheckersGame::with_draw_limit(DEFAULT_DRAW_LIMIT)
+    }
+}
+
+impl CheckersGame {
+    pub fn new() -> Self {
+        CheckersGame::default()
+    }
+
+    /// Create a game that is drawn after the specified number of turns without a capture.
+    pub fn with_draw_limit(draw_limit: u32) -> Self {
+        CheckersGame {
+            board: Board::new(),
+            jumping_piece: None,
+            next_index: 0,
+            turns_without_capture: 0,
+            captured_this_turn: false,
+            draw_limit,
+            num_turns: [0, 0],
+        }
+    }
+
+    fn calculate_score(&self, num_turns: u32, outcome: i8) -> GameScore {
+        // Finishing sooner scores more, but the score is clamped so that a long game can't
+        // turn a win into a negative score.
+        let score: f32 = (100.0 - num_turns as f32).max(1.0);
+        let multiplier: f32 = match outcome {
+            x if x > 0 => 1.0,
+            x if x < 0 => -10.0,
+            _ => 0.0,
+        };
+
+        score * multiplier
+    }
+
+    fn get_possible_moves(&self, index: usize) -> Vec<u32> {
+        self.board
+            .get_possible_moves(self.get_identity(index), self.jumping_piece)
+    }
+
+    fn is_draw(&self) -> bool {
+        self.turns_without_capture >= self.draw_limit
+    }
+}
+
+impl GameObject for CheckersGame {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "board": self.board.to_json(),
+            "jumping_piece": self.jumping_piece,
+            "next_index": self.next_index,
+            "turns_without_capture": self.turns_without_capture,
+            "captured_this_turn": self.captured_this_turn,
+            "num_turns": self.num_turns
+        })
+    }
+
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let name = "checkers";
+        let mut board = self.board.clone();
+        board.from_json(require_state_part(value, name, "board")?)?;
+        let jumping_piece: Option<usize> = get_state_field(value, name, "jumping_piece")?;
+        let next_index: usize = get_state_field(value, name, "next_index")?.unwrap_or(0);
+        if next_index > 1 {
+            return Err(invalid_state(
+                name,
+                format!("next_index must be 0 or 1, got {}", next_index),
+            ));
+        }
+        if let Some(x) = jumping_piece {
+            let identity = self.get_identity(next_index);
+            if x >= 32 || Board::owner(board.getat(x)) != Some(identity) {
+                return Err(invalid_state(
+                    name,
+                    format!("jumping_piece {} is not one of {}'s pieces", x, identity),
+                ));
+            }
+        }
+
+        let turns_without_capture =
+            get_state_field(value, name, "turns_without_capture")?.unwrap_or(0);
+        let captured_this_turn =
+            get_state_field(value, name, "captured_this_turn")?.unwrap_or(false);
+        let num_turns = get_state_field(value, name, "num_turns")?.unwrap_or([0, 0]);
+
+        self.board = board;
+        self.jumping_piece = jumping_piece;
+        self.next_index = next_index;
+        self.turns_without_capture = turns_without_capture;
+        self.captured_this_turn = captured_this_turn;
+        self.num_turns = num_turns;
+        Ok(())
+    }
+}
+
+impl GameTrait for CheckersGame {
+    fn clone_box(&self) -> Box<dyn GameTrait> {
+        Box::new(self.clone())
+    }
+
+    fn get_identities(&self) -> [char; 2] {
+        ['X', 'O']
+    }
+
+    fn get_game_info(&self) -> GameInfo {
+        GameInfo {
+            input_count: 128,
+            output_count: 128,
+        }
+    }
+
+    fn get_inputs(&self, index: usize) -> (Vec<f32>, Vec<u32>) {
+        let mut inputs = Vec::with_capacity(128);
+        let identity = self.get_identity(index);
+        let other_identity = self.get_identity(1 - index);
+        for (owner, king) in &[
+            (identity, false),
+            (identity, true),
+            (other_identity, false),
+            (other_identity, true),
+        ] {
+            for square in 0..32 {
+                let piece = self.board.getat(square);
+                let matched =
+                    Board::owner(piece) == Some(*owner) && piece.is_ascii_uppercase() == *king;
+                inputs.push(if matched { 1.0 } else { 0.0 });
+            }
+        }
+
+        (inputs, self.get_possible_moves(index))
+    }
+
+    fn update(&mut self, index: usize, output: u32) {
+        let moves = self.get_possible_moves(index);
+        assert!(!moves.is_empty(), "No valid move available: {:?}", moves);
+        assert!(
+            moves.contains(&output),
+            "Illegal move {}, expected one of {:?}",
+            output,
+            moves
+        );
+        self.num_turns[index] += 1;
+
+        let result = self.board.apply_move(output);
+        if result.captured {
+            self.captured_this_turn = true;
+            if !result.kinged && !self.board.get_jumps(result.landing).is_empty() {
+                // Multi-jump: same player, same piece.
+                self.jumping_piece = Some(result.landing);
+                self.next_index = index;
+                return;
+            }
+        }
+
+        if self.captured_this_turn {
+            self.turns_without_capture = 0;
+        } else {
+            self.turns_without_capture += 1;
+        }
+        self.captured_this_turn = false;
+        self.jumping_piece = None;
+        self.next_index = 1 - index;
+    }
+
+    fn get_next_index(&self, _index: usize) -> usize {
+        self.next_index
+    }
+
+    fn get_player_to_move(&self) -> Option<usize> {
+        Some(self.next_index)
+    }
+
+    fn is_ended(&self) -> bool {
+        self.is_draw() || self.get_possible_moves(self.next_index).is_empty()
+    }
+
+    fn get_result(&self) -> GameResult {
+        let mut result = GameResult::new(self.get_identities());
+        let outcomes: [i8; 2] = if self.is_draw() {
+            result.set_tie();
+            [0, 0]
+        } else {
+            // The player to move has no legal moves, and loses.
+            result.set_win();
+            if self.next_index == 0 {
+                [-1, 1]
+            } else {
+                [1, -1]
+            }
+        };
+
+        result.set_score1(self.calculate_score(self.num_turns[0], outcomes[0]));
+        result.set_score2(self.calculate_score(self.num_turns[1], outcomes[1]));
+        result
+    }
+
+    fn show(&self, indent: u8) {
+        self.board.show(indent);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    #[test]
+    fn test_draw_rule() {
+        let mut g = CheckersGame::with_draw_limit(2);
+        let (_, moves) = g.get_inputs(0);
+        g.update(0, moves[0]);
+        assert!(!g.is_ended());
+        let (_, moves) = g.get_inputs(1);
+        g.update(1, moves[0]);
+        assert!(g.is_ended());
+        assert!(g.get_result().is_tie());
+
+        let mut g2 = CheckersGame::new();
+        g2.from_json(&g.to_json()).unwrap();
+        assert_eq!(g2.to_json(), g.to_json(), "State was imported correctly");
+    }
+
+    #[test]
+    fn test_long_game_score() {
+        let mut g = CheckersGame::new();
+        let state = json!({
+            "board": { "data": format!("x{}", " ".repeat(31)) },
+            "next_index": 1,
+            "num_turns": [150, 150]
+        });
+        g.from_json(&state).unwrap();
+        assert!(g.is_ended());
+        let result = g.get_result();
+        assert_eq!(result.get_winner_identity(), Some('X'));
+        assert!(result.get_score1() > 0.0, "Winner scored {}", result.get_score1());
+        assert!(result.get_score2() < 0.0, "Loser scored {}", result.get_score2());
+    }
+}
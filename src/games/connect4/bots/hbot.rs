@@ -1,3 +1,4 @@
+use crate::engine::errors::NaughtsResult;
 use crate::engine::gamebase::GameInfo;
 use crate::engine::gameobject::GameObject;
 use crate::engine::gameplayer::{GamePlayer, PlayerData};
@@ -7,7 +8,7 @@ use log::info;
 use serde_json;
 use std::io;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct HumanConnect4Bot {
     player_data: PlayerData,
     world: World,
@@ -29,10 +30,16 @@ impl GameObject for HumanConnect4Bot {
     fn to_json(&self) -> serde_json::Value {
         serde_json::json!({})
     }
-    fn from_json(&mut self, _data: &serde_json::Value) {}
+    fn from_json(&mut self, _data: &serde_json::Value) -> NaughtsResult<()> {
+        Ok(())
+    }
 }
 
 impl GamePlayer for HumanConnect4Bot {
+    fn clone_box(&self) -> Box<dyn GamePlayer> {
+        Box::new(self.clone())
+    }
+
     fn get_data(&self) -> &PlayerData {
         &self.player_data
     }
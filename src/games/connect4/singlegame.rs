@@ -1,3 +1,4 @@
+use crate::engine::errors::NaughtsResult;
 use crate::engine::gamebase::{GameInfo, GameTrait};
 use crate::engine::gameobject::GameObject;
 use crate::engine::gameresult::{GameResult, GameScore};
@@ -5,6 +6,7 @@ use crate::games::connect4::world::{World, WorldState};
 
 use serde_json;
 
+#[derive(Clone)]
 pub struct Connect4Game {
     world: World,
     num_turns: [u32; 2],
@@ -41,12 +43,16 @@ impl GameObject for Connect4Game {
         self.world.to_json()
     }
 
-    fn from_json(&mut self, value: &serde_json::Value) {
-        self.world.from_json(value);
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        self.world.from_json(value)
     }
 }
 
 impl GameTrait for Connect4Game {
+    fn clone_box(&self) -> Box<dyn GameTrait> {
+        Box::new(self.clone())
+    }
+
     fn get_identities(&self) -> [char; 2] {
         ['X', 'O']
     }
@@ -81,25 +87,20 @@ impl GameTrait for Connect4Game {
     fn update(&mut self, index: usize, output: u32) {
         let moves = self.world.get_possible_moves();
         assert!(!moves.is_empty(), "No valid move available: {:?}", moves);
+        assert!(
+            moves.contains(&output),
+            "Illegal move {}, expected one of {:?}",
+            output,
+            moves
+        );
         let identity = self.get_identity(index);
         self.num_turns[index] += 1;
 
-        let target_move = if moves.len() == 1 {
-            moves[0]
-        } else {
-            let mut target = moves[0];
-            let mut lowest_diff: Option<u32> = None;
-            for m in moves.iter() {
-                let diff = (output as i32 - *m as i32).abs() as u32;
-                if lowest_diff == None || diff < lowest_diff.unwrap() {
-                    lowest_diff = Some(diff);
-                    target = *m;
-                }
-            }
-            target
-        };
+        self.world.setat(output as usize, identity);
+    }
 
-        self.world.setat(target_move as usize, identity);
+    fn get_state_key(&self) -> String {
+        format!("{}{:?}", self.to_json(), self.num_turns)
     }
 
     fn is_ended(&self) -> bool {
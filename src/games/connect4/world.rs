@@ -1,4 +1,5 @@
-use crate::engine::gameobject::GameObject;
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gameobject::{invalid_state, require_state_field, GameObject};
 
 use log::info;
 use serde_json;
@@ -14,6 +15,7 @@ pub enum WorldState {
     Draw,
 }
 
+#[derive(Clone)]
 pub struct World {
     data: String,
 }
@@ -223,11 +225,21 @@ impl GameObject for World {
         })
     }
 
-    fn from_json(&mut self, value: &serde_json::Value) {
-        if let Some(x) = value.get("data").and_then(|x| x.as_str()) {
-            self.data = String::from(x);
-        } else {
-            self.data = World::empty_world()
-        };
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let data: String = require_state_field(value, "connect4 world", "data")?;
+        if data.len() != WORLD_WIDTH * WORLD_HEIGHT
+            || !data.chars().all(|c| c == ' ' || c == 'X' || c == 'O')
+        {
+            return Err(invalid_state(
+                "connect4 world",
+                format!(
+                    "expected {} cells of ' ', 'X' or 'O', got {:?}",
+                    WORLD_WIDTH * WORLD_HEIGHT,
+                    data
+                ),
+            ));
+        }
+        self.data = data;
+        Ok(())
     }
 }
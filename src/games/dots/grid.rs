@@ -0,0 +1,247 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gameobject::{invalid_state, require_state_field, GameObject};
+
+use log::info;
+use serde_json;
+
+/// A Dots and Boxes grid, measured in boxes.
+///
+/// Lines are numbered with all horizontal lines first (row by row, top to bottom), followed by
+/// all vertical lines (row by row, left to right).
+/// For a 2x1 grid:
+///
+///  +-0-+-1-+
+///  4   5   6
+///  +-2-+-3-+
+#[derive(Clone)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    lines: Vec<bool>,
+    boxes: Vec<Option<usize>>,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Grid::new(3, 3)
+    }
+}
+
+impl Grid {
+    /// Create a new empty Grid with the specified number of boxes across and down.
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(width > 0 && height > 0, "Grid must contain at least 1 box");
+        Grid {
+            width,
+            height,
+            lines: vec![false; Grid::count_lines(width, height)],
+            boxes: vec![None; width * height],
+        }
+    }
+
+    fn count_lines(width: usize, height: usize) -> usize {
+        (height + 1) * width + height * (width + 1)
+    }
+
+    pub fn num_lines(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn num_boxes(&self) -> usize {
+        self.boxes.len()
+    }
+
+    fn hline(&self, row: usize, col: usize) -> usize {
+        row * self.width + col
+    }
+
+    fn vline(&self, row: usize, col: usize) -> usize {
+        (self.height + 1) * self.width + row * (self.width + 1) + col
+    }
+
+    /// Return true if the specified line has been drawn.
+    pub fn has_line(&self, line: usize) -> bool {
+        self.lines[line]
+    }
+
+    /// Get the index of the player that completed the specified box, if any.
+    pub fn get_box_owner(&self, index: usize) -> Option<usize> {
+        self.boxes[index]
+    }
+
+    /// Get the number of boxes completed by the specified player.
+    pub fn count_boxes(&self, player: usize) -> u32 {
+        self.boxes.iter().filter(|x| **x == Some(player)).count() as u32
+    }
+
+    fn is_box_complete(&self, row: usize, col: usize) -> bool {
+        self.lines[self.hline(row, col)]
+            && self.lines[self.hline(row + 1, col)]
+            && self.lines[self.vline(row, col)]
+            && self.lines[self.vline(row, col + 1)]
+    }
+
+    /// Get the (row, col) of each box bordering the specified line.
+    fn get_adjacent_boxes(&self, line: usize) -> Vec<(usize, usize)> {
+        let mut boxes = Vec::with_capacity(2);
+        let num_hlines = (self.height + 1) * self.width;
+        if line < num_hlines {
+            let (row, col) = (line / self.width, line % self.width);
+            if row > 0 {
+                boxes.push((row - 1, col));
+            }
+            if row < self.height {
+                boxes.push((row, col));
+            }
+        } else {
+            let offset = line - num_hlines;
+            let (row, col) = (offset / (self.width + 1), offset % (self.width + 1));
+            if col > 0 {
+                boxes.push((row, col - 1));
+            }
+            if col < self.width {
+                boxes.push((row, col));
+            }
+        }
+        boxes
+    }
+
+    /// Draw the specified line for the specified player.
+    /// Returns the number of boxes completed by this line.
+    pub fn draw_line(&mut self, line: usize, player: usize) -> u32 {
+        assert!(!self.lines[line], "Line {} has already been drawn", line);
+        self.lines[line] = true;
+
+        let mut completed = 0;
+        for (row, col) in self.get_adjacent_boxes(line) {
+            if self.is_box_complete(row, col) {
+                self.boxes[row * self.width + col] = Some(player);
+                completed += 1;
+            }
+        }
+        completed
+    }
+
+    /// Return true if all lines have been drawn.
+    pub fn is_ended(&self) -> bool {
+        self.lines.iter().all(|x| *x)
+    }
+
+    /// Get all possible moves.
+    pub fn get_possible_moves(&self) -> Vec<u32> {
+        (0..self.lines.len())
+            .filter(|x| !self.lines[*x])
+            .map(|x| x as u32)
+            .collect()
+    }
+
+    /// Display this grid on the screen, marking boxes with the owner's identity.
+    pub fn show(&self, indent: u8, identities: [char; 2]) {
+        let prefix = format!("{:1$}", " ", indent as usize);
+        for row in 0..=self.height {
+            let mut line = String::from("+");
+            for col in 0..self.width {
+                line.push_str(if self.lines[self.hline(row, col)] {
+                    "---+"
+                } else {
+                    "   +"
+                });
+            }
+            info!("{}{}", prefix, line);
+
+            if row < self.height {
+                let mut line = String::new();
+                for col in 0..=self.width {
+                    line.push(if self.lines[self.vline(row, col)] {
+                        '|'
+                    } else {
+                        ' '
+                    });
+                    if col < self.width {
+                        let owner = self.boxes[row * self.width + col];
+                        line.push_str(&format!(" {} ", owner.map_or(' ', |x| identities[x])));
+                    }
+                }
+                info!("{}{}", prefix, line);
+            }
+        }
+        info!("");
+    }
+}
+
+/// GameObject lets us serialise and deserialise the contents as JSON.
+impl GameObject for Grid {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "width": self.width,
+            "height": self.height,
+            "lines": self.lines,
+            "boxes": self.boxes
+        })
+    }
+
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let name = "dots grid";
+        let width: usize = require_state_field(value, name, "width")?;
+        let height: usize = require_state_field(value, name, "height")?;
+        let lines: Vec<bool> = require_state_field(value, name, "lines")?;
+        let boxes: Vec<Option<usize>> = require_state_field(value, name, "boxes")?;
+        if width == 0 || height == 0 {
+            return Err(invalid_state(
+                name,
+                format!("{}x{} grid must contain at least 1 box", width, height),
+            ));
+        }
+        if width.checked_mul(height) != Some(boxes.len())
+            || Grid::count_lines(width, height) != lines.len()
+        {
+            return Err(invalid_state(
+                name,
+                format!(
+                    "{}x{} grid can't have {} lines and {} boxes",
+                    width,
+                    height,
+                    lines.len(),
+                    boxes.len()
+                ),
+            ));
+        }
+        if boxes.iter().any(|x| x.is_some_and(|y| y > 1)) {
+            return Err(invalid_state(
+                name,
+                String::from("box owners must be 0, 1 or null"),
+            ));
+        }
+
+        self.width = width;
+        self.height = height;
+        self.lines = lines;
+        self.boxes = boxes;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_complete_box() {
+        let mut g = Grid::new(2, 1);
+        assert_eq!(g.num_lines(), 7);
+        assert_eq!(g.draw_line(0, 0), 0);
+        assert_eq!(g.draw_line(2, 1), 0);
+        assert_eq!(g.draw_line(4, 0), 0);
+        assert_eq!(g.draw_line(1, 1), 0);
+        assert_eq!(g.draw_line(3, 0), 0);
+        // The middle line completes the first box.
+        assert_eq!(g.draw_line(5, 1), 1);
+        assert_eq!(g.get_box_owner(0), Some(1));
+        assert_eq!(g.draw_line(6, 0), 1);
+        assert_eq!(g.count_boxes(0), 1);
+        assert!(g.is_ended());
+
+        let mut g2 = Grid::new(1, 1);
+        g2.from_json(&g.to_json()).unwrap();
+        assert_eq!(g2.to_json(), g.to_json(), "State was imported correctly");
+    }
+}
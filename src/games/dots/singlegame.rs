@@ -0,0 +1,146 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gamebase::{GameInfo, GameTrait};
+use crate::engine::gameobject::{get_state_field, require_state_part, GameObject};
+use crate::engine::gameresult::{GameResult, GameScore};
+use crate::games::dots::grid::Grid;
+
+use serde_json;
+
+pub const DEFAULT_WIDTH: usize = 3;
+pub const DEFAULT_HEIGHT: usize = 3;
+
+/// Dots and Boxes.
+///
+/// Inputs are one per line (drawn or not), followed by the boxes completed by this player and the
+/// boxes completed by the opponent. Outputs are line numbers (see `Grid`).
+/// Completing a box gives the player another move.
+#[derive(Clone)]
+pub struct DotsGame {
+    grid: Grid,
+    extra_turn: bool,
+}
+
+impl Default for DotsGame {
+    fn default() -> Self {
+        DotsGame::with_size(DEFAULT_WIDTH, DEFAULT_HEIGHT)
+    }
+}
+
+impl DotsGame {
+    pub fn new() -> Self {
+        DotsGame::default()
+    }
+
+    /// Create a game with the specified number of boxes across and down.
+    pub fn with_size(width: usize, height: usize) -> Self {
+        DotsGame {
+            grid: Grid::new(width, height),
+            extra_turn: false,
+        }
+    }
+
+    /// Unlike the line-making games, the score is simply the number of boxes completed.
+    /// This rewards bots for winning by a larger margin and for limiting losses.
+    fn calculate_score(&self, index: usize) -> GameScore {
+        self.grid.count_boxes(index) as f32
+    }
+}
+
+impl GameObject for DotsGame {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "grid": self.grid.to_json(),
+            "extra_turn": self.extra_turn
+        })
+    }
+
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let mut grid = self.grid.clone();
+        grid.from_json(require_state_part(value, "dots", "grid")?)?;
+        let extra_turn = get_state_field(value, "dots", "extra_turn")?.unwrap_or(false);
+
+        self.grid = grid;
+        self.extra_turn = extra_turn;
+        Ok(())
+    }
+}
+
+impl GameTrait for DotsGame {
+    fn clone_box(&self) -> Box<dyn GameTrait> {
+        Box::new(self.clone())
+    }
+
+    fn get_identities(&self) -> [char; 2] {
+        ['A', 'B']
+    }
+
+    fn get_game_info(&self) -> GameInfo {
+        GameInfo {
+            input_count: (self.grid.num_lines() + self.grid.num_boxes() * 2) as u32,
+            output_count: self.grid.num_lines() as u32,
+        }
+    }
+
+    fn get_inputs(&self, index: usize) -> (Vec<f32>, Vec<u32>) {
+        let num_boxes = self.grid.num_boxes();
+        let mut inputs = Vec::with_capacity(self.grid.num_lines() + num_boxes * 2);
+        for line in 0..self.grid.num_lines() {
+            inputs.push(if self.grid.has_line(line) { 1.0 } else { 0.0 });
+        }
+
+        for player in &[index, 1 - index] {
+            for b in 0..num_boxes {
+                inputs.push(if self.grid.get_box_owner(b) == Some(*player) {
+                    1.0
+                } else {
+                    0.0
+                });
+            }
+        }
+
+        (inputs, self.grid.get_possible_moves())
+    }
+
+    fn update(&mut self, index: usize, output: u32) {
+        let moves = self.grid.get_possible_moves();
+        assert!(!moves.is_empty(), "No valid move available: {:?}", moves);
+        assert!(
+            moves.contains(&output),
+            "Illegal move {}, expected one of {:?}",
+            output,
+            moves
+        );
+
+        let completed = self.grid.draw_line(output as usize, index);
+        self.extra_turn = completed > 0 && !self.grid.is_ended();
+    }
+
+    fn get_next_index(&self, index: usize) -> usize {
+        if self.extra_turn {
+            index
+        } else {
+            1 - index
+        }
+    }
+
+    fn is_ended(&self) -> bool {
+        self.grid.is_ended()
+    }
+
+    fn get_result(&self) -> GameResult {
+        assert!(self.is_ended(), "BUG: get_result() called before game ended");
+        let mut result = GameResult::new(self.get_identities());
+        result.set_score1(self.calculate_score(0));
+        result.set_score2(self.calculate_score(1));
+        if result.get_winner().is_some() {
+            result.set_win();
+        } else {
+            result.set_tie();
+        }
+        result
+    }
+
+    fn show(&self, indent: u8) {
+        self.grid.show(indent, self.get_identities());
+    }
+}
@@ -0,0 +1,275 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gameobject::{invalid_state, require_state_field, GameObject};
+
+use log::info;
+use serde_json;
+
+/// Disjoint set forest used to track connected groups of stones.
+/// Union by size keeps the trees shallow, so `find()` doesn't need to mutate.
+#[derive(Clone)]
+struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        UnionFind {
+            parents: (0..size).collect(),
+            sizes: vec![1; size],
+        }
+    }
+
+    fn find(&self, index: usize) -> usize {
+        let mut root = index;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut root_a, mut root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return;
+        }
+        if self.sizes[root_a] > self.sizes[root_b] {
+            std::mem::swap(&mut root_a, &mut root_b);
+        }
+        self.parents[root_a] = root_b;
+        self.sizes[root_b] += self.sizes[root_a];
+    }
+}
+
+/// A Hex board: an NxN rhombus of hexagonal cells.
+///
+/// Cells are numbered row * size + col. X joins the top and bottom edges, O joins the left and
+/// right edges. Each cell (r, c) touches (r-1, c), (r-1, c+1), (r, c-1), (r, c+1), (r+1, c-1)
+/// and (r+1, c).
+#[derive(Clone)]
+pub struct Board {
+    size: usize,
+    cells: Vec<char>,
+    groups: UnionFind,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new(7)
+    }
+}
+
+impl Board {
+    /// Create a new empty Board with the specified side length.
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "Hex board must have at least 1 cell");
+        Board {
+            size,
+            cells: vec![' '; size * size],
+            // The 4 extra nodes are the top, bottom, left and right edges.
+            groups: UnionFind::new(size * size + 4),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn top(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn bottom(&self) -> usize {
+        self.cells.len() + 1
+    }
+
+    fn left(&self) -> usize {
+        self.cells.len() + 2
+    }
+
+    fn right(&self) -> usize {
+        self.cells.len() + 3
+    }
+
+    /// Get the character at the specified cell.
+    pub fn getat(&self, pos: usize) -> char {
+        self.cells[pos]
+    }
+
+    fn get_neighbours(&self, pos: usize) -> Vec<usize> {
+        let (row, col) = ((pos / self.size) as isize, (pos % self.size) as isize);
+        let size = self.size as isize;
+        [(-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0)]
+            .iter()
+            .map(|(dr, dc)| (row + dr, col + dc))
+            .filter(|(r, c)| *r >= 0 && *r < size && *c >= 0 && *c < size)
+            .map(|(r, c)| (r * size + c) as usize)
+            .collect()
+    }
+
+    /// Place a stone at the specified cell and join it to its neighbours.
+    pub fn setat(&mut self, pos: usize, turn: char) {
+        assert_eq!(self.cells[pos], ' ', "Cell {} is already occupied", pos);
+        self.cells[pos] = turn;
+
+        let (row, col) = (pos / self.size, pos % self.size);
+        if turn == 'X' {
+            if row == 0 {
+                self.groups.union(pos, self.top());
+            }
+            if row == self.size - 1 {
+                self.groups.union(pos, self.bottom());
+            }
+        } else {
+            if col == 0 {
+                self.groups.union(pos, self.left());
+            }
+            if col == self.size - 1 {
+                self.groups.union(pos, self.right());
+            }
+        }
+
+        for n in self.get_neighbours(pos) {
+            if self.cells[n] == turn {
+                self.groups.union(pos, n);
+            }
+        }
+    }
+
+    /// Rebuild the board from scratch, recalculating all groups.
+    fn set_cells(&mut self, cells: Vec<char>) {
+        let size = (cells.len() as f64).sqrt() as usize;
+        *self = Board::new(size);
+        for (pos, c) in cells.into_iter().enumerate() {
+            if c == 'X' || c == 'O' {
+                self.setat(pos, c);
+            }
+        }
+    }
+
+    /// Get the position of the only stone on the board, mirrored across the long diagonal.
+    /// This is where the stone goes when the second player swaps.
+    pub fn get_swap_position(&self) -> Option<usize> {
+        let stones: Vec<usize> = (0..self.cells.len())
+            .filter(|x| self.cells[*x] != ' ')
+            .collect();
+        match stones.as_slice() {
+            [pos] => Some((pos % self.size) * self.size + pos / self.size),
+            _ => None,
+        }
+    }
+
+    /// Replace the only stone on the board with a mirrored stone for the other player.
+    pub fn swap(&mut self, turn: char) {
+        let target = self
+            .get_swap_position()
+            .expect("Swap is only allowed with exactly 1 stone on the board");
+        let mut cells = vec![' '; self.cells.len()];
+        cells[target] = turn;
+        self.set_cells(cells);
+    }
+
+    /// Get the winner, if there is one. Hex cannot end in a draw.
+    pub fn get_winner(&self) -> Option<char> {
+        if self.groups.find(self.top()) == self.groups.find(self.bottom()) {
+            Some('X')
+        } else if self.groups.find(self.left()) == self.groups.find(self.right()) {
+            Some('O')
+        } else {
+            None
+        }
+    }
+
+    /// Get all empty cells.
+    pub fn get_possible_moves(&self) -> Vec<u32> {
+        (0..self.cells.len())
+            .filter(|x| self.cells[*x] == ' ')
+            .map(|x| x as u32)
+            .collect()
+    }
+
+    /// Display this board on the screen as a rhombus, with each row shifted right.
+    pub fn show(&self, indent: u8) {
+        let prefix = format!("{:1$}", " ", indent as usize);
+        let header: Vec<String> = (0..self.size).map(|x| format!("{:<3}", x)).collect();
+        info!("{}     {}  (X: top-bottom, O: left-right)", prefix, header.join(" "));
+        for row in 0..self.size {
+            let cells: Vec<String> = (0..self.size)
+                .map(|col| match self.cells[row * self.size + col] {
+                    ' ' => String::from("."),
+                    c => c.to_string(),
+                })
+                .collect();
+            info!(
+                "{}{:4}{} {}",
+                prefix,
+                row * self.size,
+                " ".repeat(row * 2),
+                cells.join("   ")
+            );
+        }
+        info!("");
+    }
+}
+
+/// GameObject lets us serialise and deserialise the contents as JSON.
+impl GameObject for Board {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+          "data": self.cells.iter().collect::<String>()
+        })
+    }
+
+    /// Load the cells. The board size is the square root of the number of cells.
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let data: String = require_state_field(value, "hex board", "data")?;
+        let cells: Vec<char> = data.chars().collect();
+        let size = (cells.len() as f64).sqrt() as usize;
+        if size == 0 || size * size != cells.len() {
+            return Err(invalid_state(
+                "hex board",
+                format!("{} cells is not a square board", cells.len()),
+            ));
+        }
+        if let Some(c) = cells.iter().find(|c| !matches!(c, ' ' | 'X' | 'O')) {
+            return Err(invalid_state(
+                "hex board",
+                format!("invalid cell {:?}, expected ' ', 'X' or 'O'", c),
+            ));
+        }
+        self.set_cells(cells);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_winner() {
+        let mut b = Board::new(3);
+        // X zig-zags from top to bottom: (0,1), (1,0), (2,0).
+        b.setat(1, 'X');
+        b.setat(3, 'X');
+        assert_eq!(b.get_winner(), None);
+        b.setat(6, 'X');
+        assert_eq!(b.get_winner(), Some('X'));
+
+        let mut b2 = Board::new(3);
+        b2.from_json(&b.to_json()).unwrap();
+        assert_eq!(b2.get_winner(), Some('X'), "Groups were rebuilt on import");
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut b = Board::new(3);
+        b.setat(1, 'X');
+        b.swap('O');
+        assert_eq!(b.getat(1), ' ');
+        assert_eq!(b.getat(3), 'O');
+    }
+}
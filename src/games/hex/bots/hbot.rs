@@ -0,0 +1,99 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gamebase::GameInfo;
+use crate::engine::gameobject::GameObject;
+use crate::engine::gameplayer::{GamePlayer, PlayerData};
+use crate::games::hex::board::Board;
+
+use log::info;
+use serde_json;
+use std::io;
+
+#[derive(Clone)]
+pub struct HumanHexBot {
+    player_data: PlayerData,
+    board: Board,
+}
+
+impl HumanHexBot {
+    pub fn new(game_info: &GameInfo) -> Self {
+        let size = ((game_info.input_count / 2) as f64).sqrt() as usize;
+        HumanHexBot {
+            player_data: PlayerData {
+                name: String::from("HumanHexBot"),
+                should_show_result: true,
+                ..Default::default()
+            },
+            board: Board::new(size),
+        }
+    }
+}
+
+impl GameObject for HumanHexBot {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({})
+    }
+    fn from_json(&mut self, _data: &serde_json::Value) -> NaughtsResult<()> {
+        Ok(())
+    }
+}
+
+impl GamePlayer for HumanHexBot {
+    fn clone_box(&self) -> Box<dyn GamePlayer> {
+        Box::new(self.clone())
+    }
+
+    fn get_data(&self) -> &PlayerData {
+        &self.player_data
+    }
+
+    fn get_data_mut(&mut self) -> &mut PlayerData {
+        &mut self.player_data
+    }
+
+    fn process(&mut self, inputs: Vec<f32>, available_moves: &[u32]) -> u32 {
+        let num_cells = self.board.num_cells();
+        self.board = Board::new(self.board.size());
+        for pos in 0..num_cells {
+            if inputs[pos] > 0.0 {
+                self.board.setat(pos, self.get_identity());
+            } else if inputs[pos + num_cells] > 0.0 {
+                self.board.setat(pos, self.get_other_identity());
+            }
+        }
+
+        self.board.show(4);
+
+        let swap_move = num_cells as u32;
+        let can_swap = available_moves.contains(&swap_move);
+        let prompt = format!(
+            "Enter a cell number (row start + column){}",
+            if can_swap { ", or 'swap'" } else { "" }
+        );
+
+        if available_moves.len() == 1 {
+            info!("{} (Automatically choose {})", prompt, available_moves[0]);
+            return available_moves[0];
+        }
+
+        loop {
+            println!();
+            let mut answer = String::new();
+            println!("{}: ", prompt);
+            io::stdin()
+                .read_line(&mut answer)
+                .expect("Error reading user input");
+            let answer = answer.trim();
+            if can_swap && answer.eq_ignore_ascii_case("swap") {
+                return swap_move;
+            }
+            match answer.parse::<u32>() {
+                Ok(x) => {
+                    if x != swap_move && available_moves.contains(&x) {
+                        return x;
+                    }
+                }
+                _ => continue,
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gamebase::{GameInfo, GameTrait};
+use crate::engine::gameobject::{get_state_field, require_state_part, GameObject};
+use crate::engine::gameresult::{GameResult, GameScore};
+use crate::games::hex::board::Board;
+
+use serde_json;
+
+pub const DEFAULT_SIZE: usize = 7;
+
+/// Hex.
+///
+/// Inputs are one per cell for this player's stones, followed by one per cell for the opponent's
+/// stones. Outputs are cell numbers (row * size + col). When the swap rule is enabled, there is
+/// one extra output (size * size) which is only available on the second move. Choosing it swaps
+/// the first stone: it is replaced by the second player's stone mirrored across the long diagonal.
+#[derive(Clone)]
+pub struct HexGame {
+    board: Board,
+    swap_rule: bool,
+    num_turns: [u32; 2],
+}
+
+impl Default for HexGame {
+    fn default() -> Self {
+        HexGame::with_config(DEFAULT_SIZE, true)
+    }
+}
+
+impl HexGame {
+    pub fn new() -> Self {
+        HexGame::default()
+    }
+
+    /// Create a game with the specified board size, optionally enabling the swap rule.
+    pub fn with_config(size: usize, swap_rule: bool) -> Self {
+        HexGame {
+            board: Board::new(size),
+            swap_rule,
+            num_turns: [0, 0],
+        }
+    }
+
+    fn swap_move(&self) -> u32 {
+        self.board.num_cells() as u32
+    }
+
+    fn calculate_score(&self, num_turns: u32, outcome: i8) -> GameScore {
+        let score: f32 = self.board.num_cells() as f32 - num_turns as f32;
+        let multiplier: f32 = match outcome {
+            x if x > 0 => 1.0,
+            x if x < 0 => -10.0,
+            _ => 0.0,
+        };
+
+        score * multiplier
+    }
+
+    fn get_possible_moves(&self) -> Vec<u32> {
+        let mut moves = self.board.get_possible_moves();
+        if self.swap_rule && self.num_turns == [1, 0] {
+            moves.push(self.swap_move());
+        }
+        moves
+    }
+}
+
+impl GameObject for HexGame {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "board": self.board.to_json(),
+            "swap_rule": self.swap_rule,
+            "num_turns": self.num_turns
+        })
+    }
+
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let mut board = self.board.clone();
+        board.from_json(require_state_part(value, "hex", "board")?)?;
+        let swap_rule = get_state_field(value, "hex", "swap_rule")?.unwrap_or(self.swap_rule);
+        let num_turns = get_state_field(value, "hex", "num_turns")?.unwrap_or([0, 0]);
+
+        self.board = board;
+        self.swap_rule = swap_rule;
+        self.num_turns = num_turns;
+        Ok(())
+    }
+}
+
+impl GameTrait for HexGame {
+    fn clone_box(&self) -> Box<dyn GameTrait> {
+        Box::new(self.clone())
+    }
+
+    fn get_identities(&self) -> [char; 2] {
+        ['X', 'O']
+    }
+
+    fn get_game_info(&self) -> GameInfo {
+        let num_cells = self.board.num_cells() as u32;
+        GameInfo {
+            input_count: num_cells * 2,
+            output_count: if self.swap_rule {
+                num_cells + 1
+            } else {
+                num_cells
+            },
+        }
+    }
+
+    fn get_inputs(&self, index: usize) -> (Vec<f32>, Vec<u32>) {
+        let num_cells = self.board.num_cells();
+        let mut inputs = Vec::with_capacity(num_cells * 2);
+        let identity = self.get_identity(index);
+        for pos in 0..num_cells {
+            let c = self.board.getat(pos);
+            inputs.push(if c == identity { 1.0 } else { 0.0 });
+        }
+
+        for pos in 0..num_cells {
+            let c = self.board.getat(pos);
+            inputs.push(if c == identity || c == ' ' { 0.0 } else { 1.0 });
+        }
+
+        (inputs, self.get_possible_moves())
+    }
+
+    fn update(&mut self, index: usize, output: u32) {
+        let moves = self.get_possible_moves();
+        assert!(!moves.is_empty(), "No valid move available: {:?}", moves);
+        assert!(
+            moves.contains(&output),
+            "Illegal move {}, expected one of {:?}",
+            output,
+            moves
+        );
+        let identity = self.get_identity(index);
+        self.num_turns[index] += 1;
+
+        if output == self.swap_move() {
+            self.board.swap(identity);
+        } else {
+            self.board.setat(output as usize, identity);
+        }
+    }
+
+    fn is_ended(&self) -> bool {
+        self.board.get_winner().is_some()
+    }
+
+    fn get_result(&self) -> GameResult {
+        let mut result = GameResult::new(self.get_identities());
+        let outcomes: [i8; 2] = match self.board.get_winner() {
+            Some('X') => [1, -1],
+            Some('O') => [-1, 1],
+            x => panic!("BUG: Invalid game outcome returned: {:?}", x),
+        };
+        result.set_win();
+
+        result.set_score1(self.calculate_score(self.num_turns[0], outcomes[0]));
+        result.set_score2(self.calculate_score(self.num_turns[1], outcomes[1]));
+        result
+    }
+
+    fn show(&self, indent: u8) {
+        self.board.show(indent);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gameobject::{invalid_state, require_state_field, GameObject};
+
+use log::info;
+use serde_json;
+
+/// A Kalah board.
+///
+/// The board is stored as a single list of seed counts, in sowing order:
+/// player 0 pits, player 0 store, player 1 pits, player 1 store.
+/// Pits are numbered 0 to (num_pits - 1) from each player's own point of view, with pit 0 being
+/// the furthest from their store.
+#[derive(Clone)]
+pub struct Board {
+    num_pits: usize,
+    data: Vec<u32>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new(6, 4)
+    }
+}
+
+impl Board {
+    /// Create a new Board with the specified number of pits per player and seeds per pit.
+    pub fn new(num_pits: usize, num_seeds: u32) -> Self {
+        assert!(num_pits > 0, "Kalah board must have at least 1 pit per player");
+        let mut data = vec![num_seeds; (num_pits + 1) * 2];
+        data[num_pits] = 0;
+        data[num_pits * 2 + 1] = 0;
+        Board { num_pits, data }
+    }
+
+    pub fn num_pits(&self) -> usize {
+        self.num_pits
+    }
+
+    fn pit_index(&self, player: usize, pit: usize) -> usize {
+        player * (self.num_pits + 1) + pit
+    }
+
+    fn store_index(&self, player: usize) -> usize {
+        player * (self.num_pits + 1) + self.num_pits
+    }
+
+    /// Get the number of seeds in the specified pit.
+    pub fn get_pit(&self, player: usize, pit: usize) -> u32 {
+        self.data[self.pit_index(player, pit)]
+    }
+
+    /// Get the number of seeds in the specified player's store.
+    pub fn get_store(&self, player: usize) -> u32 {
+        self.data[self.store_index(player)]
+    }
+
+    /// Get all possible moves for the specified player.
+    pub fn get_possible_moves(&self, player: usize) -> Vec<u32> {
+        (0..self.num_pits)
+            .filter(|x| self.get_pit(player, *x) > 0)
+            .map(|x| x as u32)
+            .collect()
+    }
+
+    /// Return true if either player has run out of seeds in their pits.
+    pub fn is_ended(&self) -> bool {
+        (0..2).any(|p| (0..self.num_pits).all(|x| self.get_pit(p, x) == 0))
+    }
+
+    /// Sow the seeds from the specified pit.
+    ///
+    /// Seeds are dropped one at a time anti-clockwise, skipping the opponent's store.
+    /// If the last seed lands in an empty pit owned by the player, it captures itself and any
+    /// seeds in the opposite pit. When the game ends, all remaining seeds go to the owner's store.
+    ///
+    /// Returns true if the last seed landed in the player's own store (an extra turn).
+    pub fn sow(&mut self, player: usize, pit: usize) -> bool {
+        let start = self.pit_index(player, pit);
+        let mut seeds = self.data[start];
+        assert!(seeds > 0, "Cannot sow from empty pit {}", pit);
+        self.data[start] = 0;
+
+        let opponent_store = self.store_index(1 - player);
+        let mut pos = start;
+        while seeds > 0 {
+            pos = (pos + 1) % self.data.len();
+            if pos == opponent_store {
+                continue;
+            }
+            self.data[pos] += 1;
+            seeds -= 1;
+        }
+
+        let own_store = self.store_index(player);
+        let own_pits = self.pit_index(player, 0)..own_store;
+        if own_pits.contains(&pos) && self.data[pos] == 1 {
+            let opposite = self.pit_index(1 - player, self.num_pits - 1 - (pos - own_pits.start));
+            if self.data[opposite] > 0 {
+                self.data[own_store] += self.data[opposite] + 1;
+                self.data[opposite] = 0;
+                self.data[pos] = 0;
+            }
+        }
+
+        if self.is_ended() {
+            for p in 0..2 {
+                let store = self.store_index(p);
+                for x in 0..self.num_pits {
+                    let index = self.pit_index(p, x);
+                    self.data[store] += self.data[index];
+                    self.data[index] = 0;
+                }
+            }
+            return false;
+        }
+
+        pos == own_store
+    }
+
+    /// Display this board on the screen, with player 0 along the bottom.
+    pub fn show(&self, indent: u8) {
+        let prefix = format!("{:1$}", " ", indent as usize);
+        let top: Vec<String> = (0..self.num_pits)
+            .rev()
+            .map(|x| format!("{:2}", self.get_pit(1, x)))
+            .collect();
+        let bottom: Vec<String> = (0..self.num_pits)
+            .map(|x| format!("{:2}", self.get_pit(0, x)))
+            .collect();
+        let padding = " ".repeat(self.num_pits * 5);
+        info!("{}     [{}]", prefix, top.join("] ["));
+        info!(
+            "{}[{:2}]{} [{:2}]",
+            prefix,
+            self.get_store(1),
+            padding,
+            self.get_store(0)
+        );
+        info!("{}     [{}]", prefix, bottom.join("] ["));
+        info!("");
+    }
+}
+
+/// GameObject lets us serialise and deserialise the contents as JSON.
+impl GameObject for Board {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+          "data": self.data.clone()
+        })
+    }
+
+    /// Load the seed counts. The number of pits is worked out from the number of counts.
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let data: Vec<u32> = require_state_field(value, "kalah board", "data")?;
+        let num_pits = (data.len() / 2).saturating_sub(1);
+        if num_pits == 0 || (num_pits + 1) * 2 != data.len() {
+            return Err(invalid_state(
+                "kalah board",
+                format!(
+                    "expected the pits and store for each player, got {} counts",
+                    data.len()
+                ),
+            ));
+        }
+        self.num_pits = num_pits;
+        self.data = data;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_extra_turn() {
+        let mut b = Board::new(6, 4);
+        // Pit 2 has 4 seeds, so the last one lands in the store.
+        assert!(b.sow(0, 2));
+        assert_eq!(b.get_store(0), 1);
+        assert!(!b.sow(0, 0));
+    }
+
+    #[test]
+    fn test_capture() {
+        let mut b = Board::new(6, 4);
+        b.data = vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0];
+        // Last seed lands in empty pit 1, capturing the 7 seeds in the opposite pit.
+        assert!(!b.sow(0, 0));
+        assert_eq!(b.get_store(0), 8);
+        assert_eq!(b.get_pit(1, 4), 0);
+        assert!(b.is_ended());
+    }
+}
@@ -0,0 +1,185 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gamebase::{GameInfo, GameTrait};
+use crate::engine::gameobject::{get_state_field, require_state_part, GameObject};
+use crate::engine::gameresult::{GameResult, GameScore};
+use crate::games::kalah::board::Board;
+
+use serde_json;
+
+pub const DEFAULT_PITS: usize = 6;
+pub const DEFAULT_SEEDS: u32 = 4;
+
+/// Kalah (Mancala).
+///
+/// Inputs are the seed counts (not booleans) of the player's own pits and store, followed by the
+/// opponent's pits and store. Outputs are the player's own pit numbers.
+/// Landing the last seed in your own store gives you another turn.
+#[derive(Clone)]
+pub struct KalahGame {
+    board: Board,
+    num_seeds: u32,
+    extra_turn: bool,
+    num_turns: [u32; 2],
+}
+
+impl Default for KalahGame {
+    fn default() -> Self {
+        KalahGame::with_config(DEFAULT_PITS, DEFAULT_SEEDS)
+    }
+}
+
+impl KalahGame {
+    pub fn new() -> Self {
+        KalahGame::default()
+    }
+
+    /// Create a game with the specified number of pits per player and seeds per pit.
+    pub fn with_config(num_pits: usize, num_seeds: u32) -> Self {
+        KalahGame {
+            board: Board::new(num_pits, num_seeds),
+            num_seeds,
+            extra_turn: false,
+            num_turns: [0, 0],
+        }
+    }
+
+    fn calculate_score(&self, num_turns: u32, outcome: i8) -> GameScore {
+        let total_seeds = self.board.num_pits() as u32 * self.num_seeds * 2;
+        // Clamped so that a long game can't turn a win into a negative score.
+        let score: f32 = (total_seeds as f32 - num_turns as f32).max(1.0);
+        let multiplier: f32 = match outcome {
+            x if x > 0 => 1.0,
+            x if x < 0 => -10.0,
+            _ => 0.0,
+        };
+
+        score * multiplier
+    }
+}
+
+impl GameObject for KalahGame {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "board": self.board.to_json(),
+            "seeds": self.num_seeds,
+            "extra_turn": self.extra_turn,
+            "num_turns": self.num_turns
+        })
+    }
+
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let mut board = self.board.clone();
+        board.from_json(require_state_part(value, "kalah", "board")?)?;
+        let num_seeds = get_state_field(value, "kalah", "seeds")?.unwrap_or(self.num_seeds);
+        let extra_turn = get_state_field(value, "kalah", "extra_turn")?.unwrap_or(false);
+        let num_turns = get_state_field(value, "kalah", "num_turns")?.unwrap_or([0, 0]);
+
+        self.board = board;
+        self.num_seeds = num_seeds;
+        self.extra_turn = extra_turn;
+        self.num_turns = num_turns;
+        Ok(())
+    }
+}
+
+impl GameTrait for KalahGame {
+    fn clone_box(&self) -> Box<dyn GameTrait> {
+        Box::new(self.clone())
+    }
+
+    fn get_identities(&self) -> [char; 2] {
+        ['S', 'N']
+    }
+
+    fn get_game_info(&self) -> GameInfo {
+        let num_pits = self.board.num_pits() as u32;
+        GameInfo {
+            input_count: (num_pits + 1) * 2,
+            output_count: num_pits,
+        }
+    }
+
+    fn get_inputs(&self, index: usize) -> (Vec<f32>, Vec<u32>) {
+        let num_pits = self.board.num_pits();
+        let mut inputs = Vec::with_capacity((num_pits + 1) * 2);
+        for player in &[index, 1 - index] {
+            for pit in 0..num_pits {
+                inputs.push(self.board.get_pit(*player, pit) as f32);
+            }
+            inputs.push(self.board.get_store(*player) as f32);
+        }
+
+        (inputs, self.board.get_possible_moves(index))
+    }
+
+    fn update(&mut self, index: usize, output: u32) {
+        let moves = self.board.get_possible_moves(index);
+        assert!(!moves.is_empty(), "No valid move available: {:?}", moves);
+        assert!(
+            moves.contains(&output),
+            "Illegal move {}, expected one of {:?}",
+            output,
+            moves
+        );
+        self.num_turns[index] += 1;
+
+        self.extra_turn = self.board.sow(index, output as usize);
+    }
+
+    fn get_next_index(&self, index: usize) -> usize {
+        if self.extra_turn {
+            index
+        } else {
+            1 - index
+        }
+    }
+
+    fn is_ended(&self) -> bool {
+        self.board.is_ended()
+    }
+
+    fn get_result(&self) -> GameResult {
+        assert!(self.is_ended(), "BUG: get_result() called before game ended");
+        let mut result = GameResult::new(self.get_identities());
+        let stores = [self.board.get_store(0), self.board.get_store(1)];
+        let outcomes: [i8; 2] = if stores[0] > stores[1] {
+            result.set_win();
+            [1, -1]
+        } else if stores[1] > stores[0] {
+            result.set_win();
+            [-1, 1]
+        } else {
+            result.set_tie();
+            [0, 0]
+        };
+
+        result.set_score1(self.calculate_score(self.num_turns[0], outcomes[0]));
+        result.set_score2(self.calculate_score(self.num_turns[1], outcomes[1]));
+        result
+    }
+
+    fn show(&self, indent: u8) {
+        self.board.show(indent);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_long_game_score() {
+        let mut g = KalahGame::new();
+        let state = json!({
+            "board": { "data": [0, 0, 0, 0, 0, 0, 30, 0, 0, 0, 0, 0, 0, 18] },
+            "num_turns": [60, 60]
+        });
+        g.from_json(&state).unwrap();
+        assert!(g.is_ended());
+        let result = g.get_result();
+        assert_eq!(result.get_winner_identity(), Some('S'));
+        assert!(result.get_score1() > 0.0, "Winner scored {}", result.get_score1());
+        assert!(result.get_score2() < 0.0, "Loser scored {}", result.get_score2());
+    }
+}
@@ -1,10 +1,12 @@
-use crate::engine::gameobject::GameObject;
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gameobject::{invalid_state, require_state_field, GameObject};
 
 use log::info;
 use serde_json;
 ///
 /// It consists of 9 characters, 3 lots of 3, reading left to right, top to bottom.
 /// - is a blank space. X and O are represented by those letters (uppercase).
+#[derive(Clone)]
 pub struct Board {
     data: String,
 }
@@ -203,12 +205,16 @@ impl GameObject for Board {
         })
     }
 
-    fn from_json(&mut self, value: &serde_json::Value) {
-        if let Some(x) = value.get("data").and_then(|x| x.as_str()) {
-            self.data = String::from(x);
-        } else {
-            self.data = String::from("---------")
-        };
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let data: String = require_state_field(value, "naughts board", "data")?;
+        if data.len() != 9 || !data.chars().all(|c| c == '-' || c == 'X' || c == 'O') {
+            return Err(invalid_state(
+                "naughts board",
+                format!("expected 9 cells of '-', 'X' or 'O', got {:?}", data),
+            ));
+        }
+        self.data = data;
+        Ok(())
     }
 }
 
@@ -221,7 +227,7 @@ mod tests {
         let state1 = b.to_json();
         println!("State1 = {}", state1);
         let mut b2 = Board::new();
-        b2.from_json(&state1);
+        b2.from_json(&state1).unwrap();
 
         assert_eq!(b.to_json(), state1, "State was exported the same twice");
         assert_eq!(b2.to_json(), state1, "State was imported correctly");
@@ -1,3 +1,4 @@
+use crate::engine::errors::NaughtsResult;
 use crate::engine::gamebase::GameInfo;
 use crate::engine::gameobject::GameObject;
 use crate::engine::gameplayer::{GamePlayer, PlayerData};
@@ -7,7 +8,7 @@ use log::info;
 use serde_json;
 use std::io;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct HumanBot {
     player_data: PlayerData,
     board: Board,
@@ -29,10 +30,16 @@ impl GameObject for HumanBot {
     fn to_json(&self) -> serde_json::Value {
         serde_json::json!({})
     }
-    fn from_json(&mut self, _data: &serde_json::Value) {}
+    fn from_json(&mut self, _data: &serde_json::Value) -> NaughtsResult<()> {
+        Ok(())
+    }
 }
 
 impl GamePlayer for HumanBot {
+    fn clone_box(&self) -> Box<dyn GamePlayer> {
+        Box::new(self.clone())
+    }
+
     fn get_data(&self) -> &PlayerData {
         &self.player_data
     }
@@ -1,8 +1,10 @@
+use crate::engine::errors::NaughtsResult;
 use crate::engine::gamebase::{GameInfo, GameTrait};
 use crate::engine::gameobject::GameObject;
 use crate::engine::gameresult::{GameResult, GameScore};
 use crate::games::naughts::board::Board;
 
+#[derive(Clone)]
 pub struct NaughtsGame {
     board: Board,
     num_turns: [u32; 2],
@@ -39,12 +41,16 @@ impl GameObject for NaughtsGame {
         self.board.to_json()
     }
 
-    fn from_json(&mut self, value: &serde_json::Value) {
-        self.board.from_json(value);
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        self.board.from_json(value)
     }
 }
 
 impl GameTrait for NaughtsGame {
+    fn clone_box(&self) -> Box<dyn GameTrait> {
+        Box::new(self.clone())
+    }
+
     fn get_identities(&self) -> [char; 2] {
         ['X', 'O']
     }
@@ -75,25 +81,20 @@ impl GameTrait for NaughtsGame {
     fn update(&mut self, index: usize, output: u32) {
         let moves = self.board.get_possible_moves();
         assert!(!moves.is_empty(), "No valid move available: {:?}", moves);
+        assert!(
+            moves.contains(&output),
+            "Illegal move {}, expected one of {:?}",
+            output,
+            moves
+        );
         let identity = self.get_identity(index);
         self.num_turns[index] += 1;
 
-        let target_move = if moves.len() == 1 {
-            moves[0]
-        } else {
-            let mut target = moves[0];
-            let mut lowest_diff: Option<u32> = None;
-            for m in moves.iter() {
-                let diff = (output as i32 - *m as i32).abs() as u32;
-                if lowest_diff == None || diff < lowest_diff.unwrap() {
-                    lowest_diff = Some(diff);
-                    target = *m;
-                }
-            }
-            target
-        };
+        self.board.setat(output as usize, identity);
+    }
 
-        self.board.setat(target_move as usize, identity);
+    fn get_state_key(&self) -> String {
+        format!("{}{:?}", self.to_json(), self.num_turns)
     }
 
     fn is_ended(&self) -> bool {
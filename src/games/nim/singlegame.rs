@@ -0,0 +1,284 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gamebase::{GameInfo, GameTrait};
+use crate::engine::gameobject::{get_state_field, invalid_state, require_state_field, GameObject};
+use crate::engine::gameresult::{GameResult, GameScore};
+
+use log::info;
+use serde_json;
+
+/// Options for a game of Nim.
+#[derive(Debug, Clone)]
+pub struct NimConfig {
+    /// The starting size of each heap.
+    pub heaps: Vec<u32>,
+    /// In misere play, the player who takes the last object loses.
+    pub misere: bool,
+    /// If set, only these counts may be taken from a heap.
+    pub subtraction_set: Option<Vec<u32>>,
+}
+
+impl Default for NimConfig {
+    fn default() -> Self {
+        NimConfig {
+            heaps: vec![3, 4, 5],
+            misere: false,
+            subtraction_set: None,
+        }
+    }
+}
+
+/// Nim and subtraction games.
+///
+/// Inputs are the size of each heap, followed by the binary digits of each heap size (least
+/// significant bit first), since optimal play depends on the nim-sum of the heaps.
+///
+/// Outputs encode a (heap, count) pair as `heap * max_take + (count - 1)`, where `max_take` is
+/// the largest heap (or the largest value in the subtraction set, if that is smaller).
+/// For example, with heaps [3, 4, 5], output 6 means "take 2 from heap 1".
+#[derive(Clone)]
+pub struct NimGame {
+    config: NimConfig,
+    heaps: Vec<u32>,
+    max_take: u32,
+    num_bits: u32,
+    last_player: Option<usize>,
+    num_turns: [u32; 2],
+}
+
+impl Default for NimGame {
+    fn default() -> Self {
+        NimGame::with_config(NimConfig::default())
+    }
+}
+
+impl NimGame {
+    pub fn new() -> Self {
+        NimGame::default()
+    }
+
+    pub fn with_config(config: NimConfig) -> Self {
+        assert!(!config.heaps.is_empty(), "Nim requires at least 1 heap");
+        let largest_heap = config.heaps.iter().cloned().max().unwrap_or(0);
+        let max_take = match config.subtraction_set {
+            Some(ref x) => x.iter().cloned().max().unwrap_or(0).min(largest_heap),
+            None => largest_heap,
+        };
+        assert!(max_take > 0, "Nim requires at least 1 possible move");
+        let num_bits = 32 - largest_heap.leading_zeros();
+        NimGame {
+            heaps: config.heaps.clone(),
+            config,
+            max_take,
+            num_bits,
+            last_player: None,
+            num_turns: [0, 0],
+        }
+    }
+
+    fn encode_move(&self, heap: usize, count: u32) -> u32 {
+        heap as u32 * self.max_take + count - 1
+    }
+
+    fn decode_move(&self, output: u32) -> (usize, u32) {
+        ((output / self.max_take) as usize, output % self.max_take + 1)
+    }
+
+    fn is_allowed(&self, count: u32) -> bool {
+        match self.config.subtraction_set {
+            Some(ref x) => x.contains(&count),
+            None => true,
+        }
+    }
+
+    pub fn get_possible_moves(&self) -> Vec<u32> {
+        let mut moves = Vec::new();
+        for (heap, size) in self.heaps.iter().enumerate() {
+            for count in 1..=(*size).min(self.max_take) {
+                if self.is_allowed(count) {
+                    moves.push(self.encode_move(heap, count));
+                }
+            }
+        }
+        moves
+    }
+
+    fn get_winner(&self) -> usize {
+        // If the first player has no moves at all, treat it as though the second player moved.
+        let last_player = self.last_player.unwrap_or(1);
+        if self.config.misere {
+            1 - last_player
+        } else {
+            last_player
+        }
+    }
+
+    fn calculate_score(&self, num_turns: u32, outcome: i8) -> GameScore {
+        let total: u32 = self.config.heaps.iter().sum();
+        let score: f32 = total as f32 - num_turns as f32;
+        let multiplier: f32 = match outcome {
+            x if x > 0 => 1.0,
+            x if x < 0 => -10.0,
+            _ => 0.0,
+        };
+
+        score * multiplier
+    }
+}
+
+impl GameObject for NimGame {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "heaps": self.heaps,
+            "last_player": self.last_player,
+            "num_turns": self.num_turns
+        })
+    }
+
+    /// Load the heaps. Heaps can only shrink, so each must be no larger than its starting size.
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let name = "nim";
+        let heaps: Vec<u32> = require_state_field(value, name, "heaps")?;
+        if heaps.len() != self.config.heaps.len()
+            || heaps
+                .iter()
+                .zip(self.config.heaps.iter())
+                .any(|(x, y)| x > y)
+        {
+            return Err(invalid_state(
+                name,
+                format!(
+                    "heaps {:?} don't fit the starting heaps {:?}",
+                    heaps, self.config.heaps
+                ),
+            ));
+        }
+        let last_player: Option<usize> = get_state_field(value, name, "last_player")?;
+        if let Some(x) = last_player.filter(|x| *x > 1) {
+            return Err(invalid_state(
+                name,
+                format!("last_player must be 0 or 1, got {}", x),
+            ));
+        }
+        let num_turns = get_state_field(value, name, "num_turns")?.unwrap_or([0, 0]);
+
+        self.heaps = heaps;
+        self.last_player = last_player;
+        self.num_turns = num_turns;
+        Ok(())
+    }
+}
+
+impl GameTrait for NimGame {
+    fn clone_box(&self) -> Box<dyn GameTrait> {
+        Box::new(self.clone())
+    }
+
+    fn get_identities(&self) -> [char; 2] {
+        ['1', '2']
+    }
+
+    fn get_game_info(&self) -> GameInfo {
+        let num_heaps = self.heaps.len() as u32;
+        GameInfo {
+            input_count: num_heaps * (self.num_bits + 1),
+            output_count: num_heaps * self.max_take,
+        }
+    }
+
+    fn get_inputs(&self, _index: usize) -> (Vec<f32>, Vec<u32>) {
+        let mut inputs = Vec::with_capacity(self.heaps.len() * (self.num_bits as usize + 1));
+        for heap in &self.heaps {
+            inputs.push(*heap as f32);
+        }
+
+        for heap in &self.heaps {
+            for bit in 0..self.num_bits {
+                inputs.push(if heap & (1 << bit) != 0 { 1.0 } else { 0.0 });
+            }
+        }
+
+        (inputs, self.get_possible_moves())
+    }
+
+    fn update(&mut self, index: usize, output: u32) {
+        let moves = self.get_possible_moves();
+        assert!(!moves.is_empty(), "No valid move available: {:?}", moves);
+        assert!(
+            moves.contains(&output),
+            "Illegal move {}, expected one of {:?}",
+            output,
+            moves
+        );
+        self.num_turns[index] += 1;
+
+        let (heap, count) = self.decode_move(output);
+        self.heaps[heap] -= count;
+        self.last_player = Some(index);
+    }
+
+    fn get_player_to_move(&self) -> Option<usize> {
+        self.last_player.map(|x| 1 - x)
+    }
+
+    fn is_ended(&self) -> bool {
+        self.get_possible_moves().is_empty()
+    }
+
+    fn get_result(&self) -> GameResult {
+        let mut result = GameResult::new(self.get_identities());
+        let outcomes: [i8; 2] = if self.get_winner() == 0 {
+            [1, -1]
+        } else {
+            [-1, 1]
+        };
+        result.set_win();
+
+        result.set_score1(self.calculate_score(self.num_turns[0], outcomes[0]));
+        result.set_score2(self.calculate_score(self.num_turns[1], outcomes[1]));
+        result
+    }
+
+    fn show(&self, indent: u8) {
+        let prefix = format!("{:1$}", " ", indent as usize);
+        for (index, heap) in self.heaps.iter().enumerate() {
+            info!("{}{:2}: {}", prefix, index, "|".repeat(*heap as usize));
+        }
+        info!("");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_moves() {
+        let mut g = NimGame::new();
+        assert_eq!(g.get_game_info().output_count, 15);
+        assert_eq!(g.get_possible_moves().len(), 12);
+
+        // Take 2 from heap 1.
+        g.update(0, 6);
+        assert_eq!(g.heaps, vec![3, 2, 5]);
+        g.update(1, g.encode_move(2, 5));
+        g.update(0, g.encode_move(0, 3));
+        g.update(1, g.encode_move(1, 2));
+        assert!(g.is_ended());
+        assert_eq!(g.get_result().get_winner(), Some(1));
+    }
+
+    #[test]
+    fn test_misere_subtraction() {
+        let mut g = NimGame::with_config(NimConfig {
+            heaps: vec![5],
+            misere: true,
+            subtraction_set: Some(vec![1, 3]),
+        });
+        assert_eq!(g.get_possible_moves(), vec![0, 2]);
+        g.update(0, 2);
+        g.update(1, 0);
+        // 1 left, so player 0 must take the last object and loses.
+        g.update(0, 0);
+        assert!(g.is_ended());
+        assert_eq!(g.get_result().get_winner(), Some(1));
+    }
+}
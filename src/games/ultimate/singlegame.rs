@@ -0,0 +1,307 @@
+use crate::engine::errors::NaughtsResult;
+use crate::engine::gamebase::{GameInfo, GameTrait};
+use crate::engine::gameobject::{get_state_field, invalid_state, require_state_field, GameObject};
+use crate::engine::gameresult::{GameResult, GameScore};
+use crate::games::naughts::board::Board;
+
+use log::info;
+use serde_json;
+
+/// Ultimate tic-tac-toe.
+///
+/// The game consists of 9 naughts boards arranged in a 3x3 grid. The cell you play in decides
+/// which sub-board your opponent must play in next. If that sub-board is already finished, the
+/// opponent may play in any open sub-board. Win 3 sub-boards in a row to win the game.
+///
+/// Moves are numbered board_index * 9 + cell_index, where both indexes follow the naughts
+/// layout (0-8, left to right, top to bottom).
+#[derive(Clone)]
+pub struct UltimateGame {
+    boards: Vec<Board>,
+    active_board: Option<usize>,
+    num_turns: [u32; 2],
+}
+
+impl Default for UltimateGame {
+    fn default() -> Self {
+        UltimateGame {
+            boards: (0..9).map(|_| Board::default()).collect(),
+            active_board: None,
+            num_turns: [0, 0],
+        }
+    }
+}
+
+impl UltimateGame {
+    pub fn new() -> Self {
+        UltimateGame::default()
+    }
+
+    fn calculate_score(&self, num_turns: u32, outcome: i8) -> GameScore {
+        let score: f32 = 50.0 - num_turns as f32;
+        let multiplier: f32 = match outcome {
+            x if x > 0 => 1.0,
+            x if x < 0 => -10.0,
+            _ => 0.0,
+        };
+
+        score * multiplier
+    }
+
+    /// Get the character at the specified position (0-80).
+    pub fn getat(&self, pos: usize) -> char {
+        self.boards[pos / 9].getat(pos % 9)
+    }
+
+    /// Get a board containing the winner of each sub-board.
+    fn get_meta_board(&self) -> Board {
+        let mut meta = Board::new();
+        for (index, board) in self.boards.iter().enumerate() {
+            match board.get_game_state() {
+                1 => meta.setat(index, 'X'),
+                2 => meta.setat(index, 'O'),
+                _ => {}
+            }
+        }
+        meta
+    }
+
+    /// Get the current game state as int.
+    /// 0 = Not completed.
+    /// 1 = X win.
+    /// 2 = O win.
+    /// 3 = draw.
+    pub fn get_game_state(&self) -> u8 {
+        match self.get_meta_board().get_game_state() {
+            1 => 1,
+            2 => 2,
+            _ => {
+                if self.boards.iter().all(|x| x.is_ended()) {
+                    3
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    /// Get the sub-boards the next move may be played in.
+    fn get_open_boards(&self) -> Vec<usize> {
+        match self.active_board {
+            Some(x) if !self.boards[x].is_ended() => vec![x],
+            _ => (0..9).filter(|x| !self.boards[*x].is_ended()).collect(),
+        }
+    }
+
+    pub fn get_possible_moves(&self) -> Vec<u32> {
+        let mut moves = Vec::with_capacity(81);
+        for board_index in self.get_open_boards() {
+            for m in self.boards[board_index].get_possible_moves() {
+                moves.push(board_index as u32 * 9 + m);
+            }
+        }
+        moves
+    }
+}
+
+impl GameObject for UltimateGame {
+    fn to_json(&self) -> serde_json::Value {
+        let boards: Vec<serde_json::Value> = self.boards.iter().map(|x| x.to_json()).collect();
+        serde_json::json!({
+            "boards": boards,
+            "active_board": self.active_board,
+            "num_turns": self.num_turns
+        })
+    }
+
+    fn from_json(&mut self, value: &serde_json::Value) -> NaughtsResult<()> {
+        let name = "ultimate";
+        let data: Vec<serde_json::Value> = require_state_field(value, name, "boards")?;
+        if data.len() != 9 {
+            return Err(invalid_state(
+                name,
+                format!("expected 9 boards, got {}", data.len()),
+            ));
+        }
+        let mut boards = Vec::with_capacity(9);
+        for x in data.iter() {
+            let mut board = Board::default();
+            board.from_json(x)?;
+            boards.push(board);
+        }
+
+        let active_board: Option<usize> = get_state_field(value, name, "active_board")?;
+        if let Some(x) = active_board.filter(|x| *x >= 9) {
+            return Err(invalid_state(
+                name,
+                format!("active_board must be 0-8, got {}", x),
+            ));
+        }
+        let num_turns = get_state_field(value, name, "num_turns")?.unwrap_or([0, 0]);
+
+        self.boards = boards;
+        self.active_board = active_board;
+        self.num_turns = num_turns;
+        Ok(())
+    }
+}
+
+impl GameTrait for UltimateGame {
+    fn clone_box(&self) -> Box<dyn GameTrait> {
+        Box::new(self.clone())
+    }
+
+    fn get_identities(&self) -> [char; 2] {
+        ['X', 'O']
+    }
+
+    fn get_game_info(&self) -> GameInfo {
+        GameInfo {
+            input_count: 171, // 81 for each player, plus 9 active board indicators.
+            output_count: 81,
+        }
+    }
+
+    fn get_inputs(&self, index: usize) -> (Vec<f32>, Vec<u32>) {
+        let mut inputs = Vec::with_capacity(171);
+        let identity = self.get_identity(index);
+        for pos in 0..81 {
+            let c = self.getat(pos);
+            inputs.push(if c == identity { 1.0 } else { 0.0 });
+        }
+
+        for pos in 0..81 {
+            let c = self.getat(pos);
+            inputs.push(if c == identity || c == ' ' { 0.0 } else { 1.0 });
+        }
+
+        let open_boards = self.get_open_boards();
+        for board_index in 0..9 {
+            inputs.push(if open_boards.contains(&board_index) {
+                1.0
+            } else {
+                0.0
+            });
+        }
+
+        (inputs, self.get_possible_moves())
+    }
+
+    fn update(&mut self, index: usize, output: u32) {
+        let moves = self.get_possible_moves();
+        assert!(!moves.is_empty(), "No valid move available: {:?}", moves);
+        assert!(
+            moves.contains(&output),
+            "Illegal move {}, expected one of {:?}",
+            output,
+            moves
+        );
+        let identity = self.get_identity(index);
+        self.num_turns[index] += 1;
+
+        let (board_index, cell) = (output as usize / 9, output as usize % 9);
+        self.boards[board_index].setat(cell, identity);
+        self.active_board = Some(cell);
+    }
+
+    fn is_ended(&self) -> bool {
+        self.get_game_state() != 0
+    }
+
+    fn get_result(&self) -> GameResult {
+        let mut result = GameResult::new(self.get_identities());
+        let outcome = self.get_game_state();
+        let mut outcomes: [i8; 2] = [0, 0];
+        match outcome {
+            1 => {
+                result.set_win();
+                outcomes = [1, -1];
+            }
+            2 => {
+                result.set_win();
+                outcomes = [-1, 1];
+            }
+            3 => {
+                result.set_tie();
+            }
+            _ => {
+                panic!("BUG: Invalid game outcome returned: {}", outcome);
+            }
+        }
+
+        result.set_score1(self.calculate_score(self.num_turns[0], outcomes[0]));
+        result.set_score2(self.calculate_score(self.num_turns[1], outcomes[1]));
+        result
+    }
+
+    fn show(&self, indent: u8) {
+        let prefix = format!("{:1$}", " ", indent as usize);
+        let open_boards = self.get_open_boards();
+        for row in 0..9 {
+            let mut line = String::new();
+            for col in 0..9 {
+                let board_index = (row / 3) * 3 + col / 3;
+                let cell = (row % 3) * 3 + col % 3;
+                let c = self.boards[board_index].getat(cell);
+                if c == ' ' && open_boards.contains(&board_index) {
+                    line.push('.');
+                } else {
+                    line.push(c);
+                }
+                if col % 3 == 2 && col < 8 {
+                    line.push_str(" | ");
+                } else if col < 8 {
+                    line.push(' ');
+                }
+            }
+            info!("{} {}", prefix, line);
+            if row % 3 == 2 && row < 8 {
+                info!("{}-------+-------+------", prefix);
+            }
+        }
+        info!("");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_roundtrip() {
+        let mut g = UltimateGame::new();
+        g.update(0, 4);
+        g.update(1, 40);
+        let state1 = g.to_json();
+        let mut g2 = UltimateGame::new();
+        g2.from_json(&state1).unwrap();
+
+        assert_eq!(g2.to_json(), state1, "State was imported correctly");
+        assert_eq!(g2.get_possible_moves(), g.get_possible_moves());
+    }
+
+    #[test]
+    fn test_active_board() {
+        let mut g = UltimateGame::new();
+        // X plays the centre of the top-left board, so O must play in the centre board.
+        g.update(0, 4);
+        assert_eq!(g.get_possible_moves(), (36..45).collect::<Vec<u32>>());
+
+        // Once a board is finished, sending a player there lets them play anywhere.
+        for cell in &[0, 1, 2] {
+            g.boards[2].setat(*cell, 'O');
+        }
+        g.active_board = Some(2);
+        assert_eq!(g.get_possible_moves().len(), 81 - 1 - 9);
+    }
+
+    #[test]
+    fn test_clone_box() {
+        let mut g = UltimateGame::new();
+        g.update(0, 4);
+        let g2 = g.clone_box();
+        g.update(1, 40);
+
+        assert_ne!(g2.to_json(), g.to_json(), "Clone is independent of the original");
+        assert_eq!(g2.get_inputs(1).1, (36..45).collect::<Vec<u32>>());
+    }
+}
@@ -0,0 +1,116 @@
+//! The naughts engine, games and bots.
+//!
+//! The `naughts` binary is a thin wrapper around this library. The most commonly used types
+//! are re-exported here, e.g.
+//!
+//! ```no_run
+//! use naughts::{create_game_factory, GameType};
+//!
+//! let game_type: GameType = "naughts".parse().unwrap();
+//! let game_factory = create_game_factory(&game_type);
+//! let game = game_factory();
+//! assert!(!game.is_ended());
+//! ```
+
+pub mod bots {
+    pub mod randombot {
+        pub mod rbot;
+    }
+    pub mod genbot3 {
+        pub mod gbot;
+        pub mod nodes;
+    }
+    pub mod omnibot {
+        pub mod obot;
+    }
+    pub mod nbot1 {
+        pub mod nbot;
+        pub mod neurons;
+    }
+    pub mod external {
+        pub mod ebot;
+    }
+}
+pub mod engine {
+    pub mod botdb;
+    pub mod botfactory;
+    pub mod botregistry;
+    pub mod errors;
+    pub mod experiment;
+    pub mod gamebase;
+    pub mod gameconfig;
+    pub mod gamefactory;
+    pub mod gameregistry;
+    pub mod gameobject;
+    pub mod gameplayer;
+    pub mod gameresult;
+    pub mod log;
+    pub mod recipe;
+    pub mod runners {
+        pub mod batchrunner;
+        pub mod gen2runner;
+        pub mod geneticrunner;
+        pub mod listrunner;
+        pub mod serverunner;
+        pub mod singlerunner;
+        pub mod tournamentrunner;
+        pub mod webrunner;
+        pub mod genetic {
+            pub mod processor;
+        }
+    }
+}
+pub mod games {
+    pub mod naughts {
+        pub mod board;
+        pub mod singlegame;
+        pub mod bots {
+            pub mod hbot;
+        }
+    }
+    pub mod connect4 {
+        pub mod singlegame;
+        pub mod world;
+        pub mod bots {
+            pub mod hbot;
+        }
+    }
+    pub mod ultimate {
+        pub mod singlegame;
+    }
+    pub mod kalah {
+        pub mod board;
+        pub mod singlegame;
+    }
+    pub mod dots {
+        pub mod grid;
+        pub mod singlegame;
+    }
+    pub mod hex {
+        pub mod board;
+        pub mod singlegame;
+        pub mod bots {
+            pub mod hbot;
+        }
+    }
+    pub mod nim {
+        pub mod singlegame;
+    }
+    pub mod checkers {
+        pub mod board;
+        pub mod singlegame;
+    }
+}
+#[cfg(feature = "python")]
+pub mod python;
+
+pub use crate::engine::botfactory::{BotFactory, BotType, DynBot};
+pub use crate::engine::botregistry::{register_bot, BotContext, BotSpec};
+pub use crate::engine::gamebase::{run_batch, run_magic_batch, run_one_game, GameInfo, GameTrait};
+pub use crate::engine::gameconfig::{BatchConfig, BotConfig, Command, GameConfig, RunMode};
+pub use crate::engine::gamefactory::{create_game_factory, GameFactory, GameType};
+pub use crate::engine::gameregistry::{register_game, GameSpec};
+pub use crate::engine::gameobject::GameObject;
+pub use crate::engine::gameplayer::{GamePlayer, PlayerData};
+pub use crate::engine::gameresult::{GameResult, GameScore};
+pub use crate::engine::recipe::RecipeEnvelope;
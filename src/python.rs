@@ -0,0 +1,652 @@
+//! Python bindings, built with the `python` feature.
+//!
+//! The extension module is built with maturin, e.g. `maturin develop --release`. Games and
+//! options are chosen by name, the same as on the command line:
+//!
+//! ```python
+//! import naughts
+//!
+//! game = naughts.Game("hex", {"size": 5})
+//! bot = naughts.Bot("randombot", game)
+//! index = 0
+//! while not game.is_ended():
+//!     game.update(index, bot.get_move(game, index))
+//!     index = game.get_next_index(index)
+//! print(game.get_result())
+//!
+//! recipe = open("recipe.json").read()
+//! print(naughts.run_batch("naughts", "genbot3", "randombot", 1000, recipe1=recipe))
+//! ```
+
+// The pyo3 macros convert every PyResult, which clippy reports in each method.
+#![allow(clippy::useless_conversion)]
+
+use pyo3::exceptions::{PyIOError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::bots::genbot3::gbot::GenBot3Config;
+use crate::bots::nbot1::nbot::NBot1Config;
+use crate::engine::botfactory::{BotFactory, BotType, DynBot};
+use crate::engine::errors::NaughtsError;
+use crate::engine::gamebase::{self, get_nearest_move, load_game, GameInfo, GameTrait};
+use crate::engine::gameconfig::{BatchConfig, BotConfig, IllegalMovePolicy, TimeoutAction};
+use crate::engine::gamefactory::{create_game_factory, GameType};
+use crate::engine::gameresult::GameResult;
+use crate::engine::recipe::{parse_recipe_str, RecipeEnvelope};
+
+impl From<NaughtsError> for PyErr {
+    fn from(e: NaughtsError) -> PyErr {
+        match e {
+            NaughtsError::Io(_) => PyIOError::new_err(e.to_string()),
+            NaughtsError::Storage(_) => PyRuntimeError::new_err(e.to_string()),
+            _ => PyValueError::new_err(e.to_string()),
+        }
+    }
+}
+
+/// Convert a Python object to JSON, via the json module.
+fn to_json_value(obj: &Bound<'_, PyAny>) -> PyResult<serde_json::Value> {
+    let text: String = obj
+        .py()
+        .import_bound("json")?
+        .call_method1("dumps", (obj,))?
+        .extract()?;
+    Ok(serde_json::from_str(&text).map_err(NaughtsError::from)?)
+}
+
+/// Convert JSON to a Python object, via the json module.
+fn from_json_value(py: Python<'_>, value: &serde_json::Value) -> PyResult<PyObject> {
+    Ok(py
+        .import_bound("json")?
+        .call_method1("loads", (value.to_string(),))?
+        .unbind())
+}
+
+/// Convert an optional Python dict to a JSON map.
+fn to_json_map(
+    obj: Option<&Bound<'_, PyAny>>,
+    name: &str,
+) -> PyResult<serde_json::Map<String, serde_json::Value>> {
+    match obj.map(to_json_value).transpose()? {
+        Some(serde_json::Value::Object(x)) => Ok(x),
+        Some(serde_json::Value::Null) | None => Ok(serde_json::Map::new()),
+        Some(x) => Err(PyValueError::new_err(format!(
+            "Expected a dict of {}, got {}",
+            name, x
+        ))),
+    }
+}
+
+fn parse_game(name: &str, options: Option<&Bound<'_, PyAny>>) -> PyResult<GameType> {
+    let mut game_type: GameType = name.parse().map_err(PyValueError::new_err)?;
+    for (name, value) in to_json_map(options, "game options")? {
+        game_type.set_option(&name, value)?;
+    }
+    Ok(game_type)
+}
+
+fn parse_bot(name: &str, game_type: &GameType) -> PyResult<BotType> {
+    let bot_type: BotType = name.parse().map_err(PyValueError::new_err)?;
+    bot_type
+        .check_game(game_type)
+        .map_err(PyValueError::new_err)?;
+    Ok(bot_type)
+}
+
+/// Load a recipe, given as a dict or as the contents of a recipe file, and check that it
+/// belongs to this bot and game.
+fn load_recipe(
+    recipe: &Bound<'_, PyAny>,
+    bot_type: &BotType,
+    game_type: &GameType,
+    game_info: &GameInfo,
+) -> PyResult<serde_json::Value> {
+    let data = match recipe.extract::<String>() {
+        Ok(x) => parse_recipe_str(&x)?,
+        Err(_) => to_json_value(recipe)?,
+    };
+    let envelope = RecipeEnvelope::from_json(&data)?;
+    envelope.check(bot_type, game_type, game_info)?;
+    Ok(envelope.payload)
+}
+
+fn new_bot_config(bot_types: [BotType; 2], game_type: &GameType) -> BotConfig {
+    BotConfig {
+        bot_names: [bot_types[0].to_string(), bot_types[1].to_string()],
+        bot_types,
+        game: game_type.clone(),
+        recipe: serde_json::Value::Null,
+        move_time_limit: 0,
+        genbot3: GenBot3Config::default(),
+        nbot1: NBot1Config::default(),
+    }
+}
+
+fn result_to_dict<'py>(py: Python<'py>, result: &GameResult) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("score1", result.get_score1())?;
+    dict.set_item("score2", result.get_score2())?;
+    dict.set_item("winner", result.get_winner())?;
+    dict.set_item("is_win", result.is_win())?;
+    dict.set_item("is_tie", result.is_tie())?;
+    dict.set_item("is_forfeit", result.is_forfeit())?;
+    dict.set_item("illegal_moves", result.get_illegal_moves().to_vec())?;
+    Ok(dict)
+}
+
+/// A single game, stepped one move at a time.
+#[pyclass(name = "Game", module = "naughts", unsendable)]
+pub struct PyGame {
+    game_type: GameType,
+    game: Box<dyn GameTrait>,
+}
+
+impl PyGame {
+    fn check_index(&self, index: usize) -> PyResult<()> {
+        if index < 2 {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err(format!(
+                "Invalid player index {}, expected 0 or 1",
+                index
+            )))
+        }
+    }
+}
+
+#[pymethods]
+impl PyGame {
+    /// Create a new game by name, with optional game options, e.g. Game("hex", {"size": 5}).
+    #[new]
+    #[pyo3(signature = (name, options=None))]
+    fn new(name: &str, options: Option<&Bound<'_, PyAny>>) -> PyResult<Self> {
+        let game_type = parse_game(name, options)?;
+        let game = create_game_factory(&game_type)();
+        Ok(PyGame { game_type, game })
+    }
+
+    #[getter]
+    fn name(&self) -> String {
+        self.game_type.to_string()
+    }
+
+    #[getter]
+    fn options(&self, py: Python<'_>) -> PyResult<PyObject> {
+        from_json_value(
+            py,
+            &serde_json::Value::Object(self.game_type.get_options().clone()),
+        )
+    }
+
+    #[getter]
+    fn identities(&self) -> (char, char) {
+        let identities = self.game.get_identities();
+        (identities[0], identities[1])
+    }
+
+    #[getter]
+    fn input_count(&self) -> u32 {
+        self.game.get_game_info().input_count
+    }
+
+    #[getter]
+    fn output_count(&self) -> u32 {
+        self.game.get_game_info().output_count
+    }
+
+    /// Get the inputs and available moves for the specified player.
+    fn get_inputs(&self, index: usize) -> PyResult<(Vec<f32>, Vec<u32>)> {
+        self.check_index(index)?;
+        Ok(self.game.get_inputs(index))
+    }
+
+    /// Play a move for the specified player. Illegal moves are rejected.
+    fn update(&mut self, index: usize, output: u32) -> PyResult<()> {
+        self.check_index(index)?;
+        if self.game.is_ended() {
+            return Err(NaughtsError::GameRule(String::from("Game is over")).into());
+        }
+
+        let (_, available_moves) = self.game.get_inputs(index);
+        if !available_moves.contains(&output) {
+            return Err(NaughtsError::GameRule(format!(
+                "Illegal move {}, expected one of {:?}",
+                output, available_moves
+            ))
+            .into());
+        }
+        self.game.update(index, output);
+        Ok(())
+    }
+
+    /// Get the index of the player to move after player `index` has moved.
+    fn get_next_index(&self, index: usize) -> PyResult<usize> {
+        self.check_index(index)?;
+        Ok(self.game.get_next_index(index))
+    }
+
+    fn is_ended(&self) -> bool {
+        self.game.is_ended()
+    }
+
+    fn get_result(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(result_to_dict(py, &self.game.get_result())?
+            .into_any()
+            .unbind())
+    }
+
+    fn get_state_key(&self) -> String {
+        self.game.get_state_key()
+    }
+
+    /// Start a new game with the same options.
+    fn reset(&mut self) {
+        self.game = create_game_factory(&self.game_type)();
+    }
+
+    fn copy(&self) -> Self {
+        PyGame {
+            game_type: self.game_type.clone(),
+            game: self.game.clone_box(),
+        }
+    }
+
+    fn __copy__(&self) -> Self {
+        self.copy()
+    }
+
+    fn to_json(&self, py: Python<'_>) -> PyResult<PyObject> {
+        from_json_value(py, &self.game.to_json())
+    }
+
+    /// Load the game state from to_json(). The state must be for the same game options.
+    /// Invalid data leaves the game unchanged.
+    #[pyo3(name = "from_json")]
+    fn load_json(&mut self, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        let game_factory = create_game_factory(&self.game_type);
+        self.game = load_game(&game_factory, &to_json_value(data)?)?;
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Game({:?})", self.game_type)
+    }
+}
+
+/// A bot, optionally loaded from a recipe.
+#[pyclass(name = "Bot", module = "naughts")]
+pub struct PyBot {
+    bot_type: BotType,
+    game_type: GameType,
+    game_info: GameInfo,
+    bot: DynBot,
+}
+
+#[pymethods]
+impl PyBot {
+    /// Create a bot by name for the specified game, e.g. Bot("nbot1", game, recipe).
+    ///
+    /// The recipe may be a dict or the contents of a recipe file. Params set the options
+    /// for new genetic bots, e.g. {"num_nodes": 20} for genbot3.
+    #[new]
+    #[pyo3(signature = (name, game, recipe=None, params=None))]
+    fn new(
+        name: &str,
+        game: &PyGame,
+        recipe: Option<&Bound<'_, PyAny>>,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let game_type = game.game_type.clone();
+        let game_info = game.game.get_game_info();
+        let bot_type = parse_bot(name, &game_type)?;
+
+        let mut bot_config = new_bot_config([bot_type.clone(), bot_type.clone()], &game_type);
+        let params = serde_json::Value::Object(to_json_map(params, "bot params")?);
+        match bot_type.get_spec().name.as_str() {
+            "genbot3" => {
+                bot_config.genbot3 = serde_json::from_value(params).map_err(NaughtsError::from)?
+            }
+            "nbot1" => {
+                bot_config.nbot1 = serde_json::from_value(params).map_err(NaughtsError::from)?
+            }
+            _ if params.as_object().is_some_and(|x| !x.is_empty()) => {
+                return Err(PyValueError::new_err(format!(
+                    "Bot {} does not take any params",
+                    bot_type
+                )))
+            }
+            _ => {}
+        }
+
+        let recipe = match recipe {
+            Some(x) => load_recipe(x, &bot_type, &game_type, &game_info)?,
+            None => serde_json::Value::Null,
+        };
+        let bot = BotFactory::new(game_info.clone(), bot_config)
+            .create_bot_with_custom_recipe(&bot_type, &recipe)?;
+        Ok(PyBot {
+            bot_type,
+            game_type,
+            game_info,
+            bot,
+        })
+    }
+
+    #[getter]
+    fn name(&self) -> String {
+        self.bot_type.to_string()
+    }
+
+    fn is_genetic(&self) -> bool {
+        self.bot.is_genetic()
+    }
+
+    fn is_magic(&self) -> bool {
+        self.bot.is_magic()
+    }
+
+    /// Choose a move for the specified player, without playing it. Illegal moves are snapped
+    /// to the nearest available move.
+    fn get_move(&mut self, game: &PyGame, index: usize) -> PyResult<u32> {
+        game.check_index(index)?;
+        if game.game_type != self.game_type {
+            return Err(PyValueError::new_err(format!(
+                "Bot is for {:?}, not {:?}",
+                self.game_type, game.game_type
+            )));
+        }
+        if game.game.is_ended() {
+            return Err(NaughtsError::GameRule(String::from("Game is over")).into());
+        }
+
+        let identities = game.game.get_identities();
+        self.bot.setup(identities[index], identities[1 - index]);
+        let (inputs, available_moves) = game.game.get_inputs(index);
+        let output = self.bot.process(inputs, &available_moves);
+        if available_moves.contains(&output) {
+            Ok(output)
+        } else {
+            Ok(get_nearest_move(&available_moves, output))
+        }
+    }
+
+    /// Get the bot's raw output for the specified inputs and available moves.
+    fn process(&mut self, inputs: Vec<f32>, available_moves: Vec<u32>) -> PyResult<u32> {
+        if available_moves.is_empty() {
+            return Err(PyValueError::new_err("No available moves"));
+        }
+        if inputs.len() != self.game_info.input_count as usize {
+            return Err(PyValueError::new_err(format!(
+                "Expected {} inputs, got {}",
+                self.game_info.input_count,
+                inputs.len()
+            )));
+        }
+        if let Some(x) = available_moves
+            .iter()
+            .find(|x| **x >= self.game_info.output_count)
+        {
+            return Err(PyValueError::new_err(format!(
+                "Invalid move {}, expected less than {}",
+                x, self.game_info.output_count
+            )));
+        }
+        Ok(self.bot.process(inputs, &available_moves))
+    }
+
+    fn mutate(&mut self) {
+        self.bot.mutate();
+    }
+
+    fn copy(&self) -> Self {
+        PyBot {
+            bot_type: self.bot_type.clone(),
+            game_type: self.game_type.clone(),
+            game_info: self.game_info.clone(),
+            bot: self.bot.clone_box(),
+        }
+    }
+
+    fn __copy__(&self) -> Self {
+        self.copy()
+    }
+
+    /// Get the bot's own JSON, without the recipe envelope.
+    fn to_json(&self, py: Python<'_>) -> PyResult<PyObject> {
+        from_json_value(py, &self.bot.to_json())
+    }
+
+    /// Get a recipe that can be saved and loaded by Bot() or the naughts command.
+    #[pyo3(signature = (metadata=None))]
+    fn to_recipe(&self, py: Python<'_>, metadata: Option<&Bound<'_, PyAny>>) -> PyResult<PyObject> {
+        let mut envelope = RecipeEnvelope::new(
+            &self.bot_type,
+            &self.game_type,
+            &self.game_info,
+            self.bot.to_json(),
+        );
+        envelope.metadata = to_json_map(metadata, "metadata")?;
+        from_json_value(py, &envelope.to_json())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Bot({:?}, {:?})", self.bot_type.to_string(), self.game_type)
+    }
+}
+
+fn new_batch_config(
+    game: &str,
+    options: Option<&Bound<'_, PyAny>>,
+    bots: [&str; 2],
+    batch_size: u32,
+) -> PyResult<BatchConfig> {
+    let game_type = parse_game(game, options)?;
+    let bot_types = [
+        parse_bot(bots[0], &game_type)?,
+        parse_bot(bots[1], &game_type)?,
+    ];
+    Ok(BatchConfig {
+        batch_size,
+        game: game_type.clone(),
+        magic: false,
+        magic_memo: false,
+        magic_depth: 0,
+        magic_samples: 0,
+        illegal_move_policy: IllegalMovePolicy::Snap,
+        illegal_move_penalty: 0.0,
+        move_time_limit: 0,
+        game_time_limit: 0,
+        timeout_action: TimeoutAction::RandomMove,
+        bot_config: new_bot_config(bot_types, &game_type),
+    })
+}
+
+/// Run the batch without holding the GIL, so that other Python threads can keep running.
+fn run_batch_with_recipes(
+    py: Python<'_>,
+    batch_config: &BatchConfig,
+    recipes: [Option<&Bound<'_, PyAny>>; 2],
+) -> PyResult<PyObject> {
+    let game_factory = create_game_factory(&batch_config.game);
+    let game_info = game_factory().get_game_info();
+    let mut bot_factory = BotFactory::new(game_info.clone(), batch_config.bot_config.clone());
+    for (index, recipe) in recipes.iter().enumerate() {
+        if let Some(x) = recipe {
+            let bot_type = &batch_config.bot_config.bot_types[index];
+            bot_factory.set_recipe(
+                index,
+                load_recipe(x, bot_type, &batch_config.game, &game_info)?,
+            );
+        }
+    }
+
+    let result =
+        py.allow_threads(|| gamebase::run_batch(batch_config, false, &game_factory, &bot_factory))?;
+    Ok(result_to_dict(py, &result)?.into_any().unbind())
+}
+
+/// Play a batch of games and get the average scores.
+#[pyfunction]
+#[pyo3(signature = (game, bot1, bot2, batch_size=100, recipe1=None, recipe2=None, options=None))]
+#[allow(clippy::too_many_arguments)]
+fn run_batch(
+    py: Python<'_>,
+    game: &str,
+    bot1: &str,
+    bot2: &str,
+    batch_size: u32,
+    recipe1: Option<&Bound<'_, PyAny>>,
+    recipe2: Option<&Bound<'_, PyAny>>,
+    options: Option<&Bound<'_, PyAny>>,
+) -> PyResult<PyObject> {
+    if batch_size == 0 {
+        return Err(PyValueError::new_err("batch_size must be at least 1"));
+    }
+
+    let batch_config = new_batch_config(game, options, [bot1, bot2], batch_size)?;
+    run_batch_with_recipes(py, &batch_config, [recipe1, recipe2])
+}
+
+/// Play every game reachable by a magic bot such as omnibot, and get the average scores.
+///
+/// See the --magic-memo, --magic-depth and --magic-samples options of the naughts command.
+#[pyfunction]
+#[pyo3(signature = (
+    game, bot1, bot2, recipe1=None, recipe2=None, options=None, memo=false, depth=0, samples=0
+))]
+#[allow(clippy::too_many_arguments)]
+fn run_magic_batch(
+    py: Python<'_>,
+    game: &str,
+    bot1: &str,
+    bot2: &str,
+    recipe1: Option<&Bound<'_, PyAny>>,
+    recipe2: Option<&Bound<'_, PyAny>>,
+    options: Option<&Bound<'_, PyAny>>,
+    memo: bool,
+    depth: u32,
+    samples: u32,
+) -> PyResult<PyObject> {
+    let mut batch_config = new_batch_config(game, options, [bot1, bot2], 1)?;
+    batch_config.magic = true;
+    batch_config.magic_memo = memo;
+    batch_config.magic_depth = depth;
+    batch_config.magic_samples = samples;
+    run_batch_with_recipes(py, &batch_config, [recipe1, recipe2])
+}
+
+#[pymodule]
+#[pyo3(name = "naughts")]
+fn naughts_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGame>()?;
+    m.add_class::<PyBot>()?;
+    m.add_function(wrap_pyfunction!(run_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(run_magic_batch, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyModule;
+
+    /// Run a Python script with the naughts module imported.
+    fn run_script(script: &str) {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new_bound(py, "naughts").unwrap();
+            naughts_module(&module).unwrap();
+            let globals = PyDict::new_bound(py);
+            globals.set_item("naughts", module).unwrap();
+            if let Err(e) = py.run_bound(script, Some(&globals), None) {
+                e.print(py);
+                panic!("Python script failed: {}", e);
+            }
+        });
+    }
+
+    #[test]
+    fn test_game() {
+        run_script(
+            r#"
+game = naughts.Game("hex", {"size": 3})
+assert (game.name, game.options, game.input_count) == ("hex", {"size": 3}, 18)
+bot = naughts.Bot("randombot", game)
+index = 0
+while not game.is_ended():
+    game.update(index, bot.get_move(game, index))
+    index = game.get_next_index(index)
+result = game.get_result()
+assert result["is_win"] and result["winner"] in (0, 1)
+
+game = naughts.Game("naughts")
+game.update(0, 4)
+saved = game.to_json()
+for bad in [{}, {"data": "X"}, {"data": "?--------"}]:
+    try:
+        game.from_json(bad)
+        raise AssertionError("Accepted %s" % bad)
+    except ValueError:
+        pass
+    assert game.to_json() == saved
+try:
+    game.update(1, 4)
+    raise AssertionError("Accepted an illegal move")
+except ValueError:
+    pass
+
+copy = game.copy()
+copy.update(1, 0)
+game.from_json(copy.to_json())
+assert game.to_json() == copy.to_json()
+"#,
+        );
+    }
+
+    #[test]
+    fn test_bots_and_batches() {
+        run_script(
+            r#"
+game = naughts.Game("naughts")
+bot = naughts.Bot("nbot1", game, params={"mutation_range": 0.5})
+bot.mutate()
+recipe = bot.to_recipe({"score": 1.0})
+assert (recipe["bot"], recipe["game"], recipe["metadata"]) == ("nbot1", "naughts", {"score": 1.0})
+assert naughts.Bot("nbot1", game, recipe).to_json() == bot.to_json()
+
+for args in [("genbot3", game, recipe), ("nbot1", naughts.Game("connect4"), recipe)]:
+    try:
+        naughts.Bot(*args)
+        raise AssertionError("Loaded a recipe for another bot or game")
+    except ValueError:
+        pass
+
+result = naughts.run_batch("naughts", "nbot1", "randombot", 10, recipe1=recipe)
+assert set(result) >= {"score1", "score2", "illegal_moves"}
+result = naughts.run_magic_batch("naughts", "omnibot", "randombot", memo=True)
+assert result["illegal_moves"] == [0, 0]
+try:
+    naughts.run_batch("naughts", "randombot", "randombot", 0)
+    raise AssertionError("Accepted an empty batch")
+except ValueError:
+    pass
+
+bot = naughts.Bot("nbot1", game)
+assert bot.process([0.0] * game.input_count, [4]) == 4
+for args in [([0.0] * 3, [4]), ([0.0] * game.input_count, [4, 9])]:
+    try:
+        bot.process(*args)
+        raise AssertionError("Accepted bad inputs %s" % (args,))
+    except ValueError:
+        pass
+for other in [naughts.Game("connect4"), naughts.Game("hex", {"size": 3})]:
+    try:
+        naughts.Bot("randombot", naughts.Game("hex")).get_move(other, 0)
+        raise AssertionError("Played a move in another game")
+    except ValueError:
+        pass
+"#,
+        );
+    }
+}
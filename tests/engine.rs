@@ -0,0 +1,428 @@
+use naughts::bots::genbot3::gbot::GenBot3Config;
+use naughts::bots::nbot1::nbot::NBot1Config;
+use naughts::bots::randombot::rbot::RandomBot;
+use naughts::engine::errors::{NaughtsError, NaughtsResult};
+use naughts::engine::gameconfig::{IllegalMovePolicy, TimeoutAction};
+use naughts::engine::gameregistry::get_games;
+use naughts::games::naughts::singlegame::NaughtsGame;
+use naughts::{
+    create_game_factory, register_bot, register_game, run_batch, run_one_game, BatchConfig,
+    BotConfig, BotFactory, BotSpec, BotType, GameObject, GamePlayer, GameResult, GameSpec,
+    GameType, PlayerData, RecipeEnvelope,
+};
+use std::sync::Once;
+use std::thread;
+use std::time::Duration;
+
+fn get_batch_config(game: GameType, bot_names: [&str; 2], batch_size: u32) -> BatchConfig {
+    let bot_config = BotConfig {
+        bot_names: [bot_names[0].to_string(), bot_names[1].to_string()],
+        bot_types: [bot_names[0].parse().unwrap(), bot_names[1].parse().unwrap()],
+        game: game.clone(),
+        recipe: serde_json::Value::Null,
+        move_time_limit: 0,
+        genbot3: GenBot3Config::default(),
+        nbot1: NBot1Config::default(),
+    };
+    BatchConfig {
+        batch_size,
+        game,
+        magic: false,
+        magic_memo: false,
+        magic_depth: 0,
+        magic_samples: 0,
+        illegal_move_policy: IllegalMovePolicy::Snap,
+        illegal_move_penalty: 0.0,
+        move_time_limit: 0,
+        game_time_limit: 0,
+        timeout_action: TimeoutAction::RandomMove,
+        bot_config,
+    }
+}
+
+/// A bot that sleeps before each move, and makes a number of illegal moves before each legal one.
+/// Its legal move is always the lowest available move.
+#[derive(Clone, Default)]
+struct StubBot {
+    player_data: PlayerData,
+    illegal_per_move: u32,
+    sleep: Duration,
+    calls: u32,
+}
+
+impl GameObject for StubBot {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({})
+    }
+    fn from_json(&mut self, _data: &serde_json::Value) -> NaughtsResult<()> {
+        Ok(())
+    }
+}
+
+impl GamePlayer for StubBot {
+    fn clone_box(&self) -> Box<dyn GamePlayer> {
+        Box::new(self.clone())
+    }
+
+    fn get_data(&self) -> &PlayerData {
+        &self.player_data
+    }
+
+    fn get_data_mut(&mut self) -> &mut PlayerData {
+        &mut self.player_data
+    }
+
+    fn process(&mut self, _inputs: Vec<f32>, available_moves: &[u32]) -> u32 {
+        thread::sleep(self.sleep);
+        self.calls += 1;
+        if self.calls.is_multiple_of(self.illegal_per_move + 1) {
+            available_moves[0]
+        } else {
+            999
+        }
+    }
+}
+
+/// Register test.illegal<N> bots, which make N illegal moves before each legal move, and
+/// test.sleep30, which takes 30 milliseconds over each move.
+fn register_stub_bots() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        for illegal_per_move in [0, 1, 2, 100].iter().cloned() {
+            let name = format!("test.illegal{}", illegal_per_move);
+            let spec = BotSpec::new(&name, "Bot that makes illegal moves", move |_| {
+                Box::new(StubBot {
+                    illegal_per_move,
+                    ..StubBot::default()
+                })
+            });
+            register_bot(spec).unwrap();
+        }
+        let spec = BotSpec::new("test.sleep30", "Bot that sleeps before each move", |_| {
+            Box::new(StubBot {
+                sleep: Duration::from_millis(30),
+                ..StubBot::default()
+            })
+        });
+        register_bot(spec).unwrap();
+    });
+}
+
+/// Play one game of naughts with the test.illegal<N> bots.
+fn play_illegal_game(bot_names: [&str; 2], policy: IllegalMovePolicy, penalty: f32) -> GameResult {
+    register_stub_bots();
+    let game_type: GameType = "naughts".parse().unwrap();
+    let mut batch_config = get_batch_config(game_type.clone(), bot_names, 1);
+    batch_config.illegal_move_policy = policy;
+    batch_config.illegal_move_penalty = penalty;
+    let game_factory = create_game_factory(&game_type);
+    let bot_factory = BotFactory::new(game_type.get_game_info(), batch_config.bot_config.clone());
+    run_one_game(&batch_config, false, &game_factory, &bot_factory).unwrap()
+}
+
+#[test]
+fn test_illegal_move_forfeit() {
+    let bots = ["test.illegal1", "test.illegal0"];
+    let result = play_illegal_game(bots, IllegalMovePolicy::Forfeit, 0.0);
+    assert!(result.is_forfeit());
+    assert_eq!(result.get_winner_identity(), Some('O'));
+    assert_eq!(result.get_illegal_moves(), [1, 0]);
+}
+
+#[test]
+fn test_illegal_move_retry() {
+    // One retry is enough for a bot that makes one illegal move before each legal move.
+    let bots = ["test.illegal1", "test.illegal0"];
+    let result = play_illegal_game(bots, IllegalMovePolicy::Retry(1), 0.0);
+    assert!(result.is_win(), "{}", result);
+    assert_eq!(result.get_winner_identity(), Some('X'));
+    assert_eq!(result.get_illegal_moves(), [4, 0]);
+
+    let bots = ["test.illegal0", "test.illegal2"];
+    let result = play_illegal_game(bots, IllegalMovePolicy::Retry(1), 0.0);
+    assert!(result.is_forfeit());
+    assert_eq!(result.get_winner_identity(), Some('X'));
+    assert_eq!(result.get_illegal_moves(), [0, 2]);
+}
+
+#[test]
+fn test_illegal_move_penalty() {
+    // X always snaps to the highest move, and wins on the bottom row.
+    let bots = ["test.illegal100", "test.illegal0"];
+    let result = play_illegal_game(bots, IllegalMovePolicy::Snap, 0.0);
+    let penalised = play_illegal_game(bots, IllegalMovePolicy::Snap, 1000.0);
+    assert_eq!(penalised.get_illegal_moves(), [3, 0]);
+    assert_eq!(penalised.get_winner_identity(), Some('X'), "{}", penalised);
+    assert_eq!(penalised.get_score1(), result.get_score1() - 3000.0);
+    assert_eq!(penalised.get_score2(), result.get_score2());
+    assert!(penalised.get_score1() < penalised.get_score2());
+}
+
+/// Play one game of naughts between test.sleep30 and randombot, with the specified time limits.
+fn play_slow_game(move_time: u64, game_time: u64, timeout_action: TimeoutAction) -> GameResult {
+    register_stub_bots();
+    let game_type: GameType = "naughts".parse().unwrap();
+    let mut batch_config = get_batch_config(game_type.clone(), ["test.sleep30", "randombot"], 1);
+    batch_config.move_time_limit = move_time;
+    batch_config.game_time_limit = game_time;
+    batch_config.timeout_action = timeout_action;
+    let game_factory = create_game_factory(&game_type);
+    let bot_factory = BotFactory::new(game_type.get_game_info(), batch_config.bot_config.clone());
+    run_one_game(&batch_config, false, &game_factory, &bot_factory).unwrap()
+}
+
+#[test]
+fn test_think_time() {
+    let result = play_slow_game(0, 0, TimeoutAction::Forfeit);
+    assert!(result.is_win() || result.is_tie(), "{}", result);
+    let think_time = result.get_think_times()[0];
+    assert!(think_time.num_moves >= 3);
+    assert!(think_time.mean() >= Duration::from_millis(30));
+    assert!(think_time.max >= Duration::from_millis(30));
+    assert!(think_time.total >= Duration::from_millis(30) * think_time.num_moves);
+    assert_eq!(think_time.timeouts, 0);
+}
+
+#[test]
+fn test_move_time_limit() {
+    // Every move is over the limit, and is replaced with a random move.
+    let result = play_slow_game(10, 0, TimeoutAction::RandomMove);
+    assert!(result.is_win() || result.is_tie(), "{}", result);
+    let think_time = result.get_think_times()[0];
+    assert_eq!(think_time.timeouts, think_time.num_moves);
+
+    let result = play_slow_game(10, 0, TimeoutAction::Forfeit);
+    assert!(result.is_forfeit());
+    assert_eq!(result.get_winner_identity(), Some('O'));
+    assert_eq!(result.get_think_times()[0].timeouts, 1);
+}
+
+#[test]
+fn test_game_time_limit() {
+    // The third move takes the bot over its time for the game. It isn't asked for any more
+    // moves after that, so the timeout is only counted once.
+    let result = play_slow_game(0, 75, TimeoutAction::RandomMove);
+    assert!(result.is_win() || result.is_tie(), "{}", result);
+    let think_time = result.get_think_times()[0];
+    assert_eq!(think_time.num_moves, 3);
+    assert_eq!(think_time.timeouts, 1);
+
+    let result = play_slow_game(0, 75, TimeoutAction::Forfeit);
+    assert!(result.is_forfeit());
+    assert_eq!(result.get_winner_identity(), Some('O'));
+    assert_eq!(result.get_think_times()[0].num_moves, 3);
+}
+
+/// Play games of naughts between an external bot running `command` and randombot.
+fn play_external_games(
+    command: &str,
+    num_games: u32,
+    move_time_limit: u64,
+    timeout_action: TimeoutAction,
+) -> Vec<GameResult> {
+    let game_type: GameType = "naughts".parse().unwrap();
+    let bot_name = format!("external:{}", command);
+    let mut batch_config = get_batch_config(game_type.clone(), [&bot_name, "randombot"], 1);
+    batch_config.move_time_limit = move_time_limit;
+    batch_config.bot_config.move_time_limit = move_time_limit;
+    batch_config.timeout_action = timeout_action;
+    let game_factory = create_game_factory(&game_type);
+    let bot_factory = BotFactory::new(game_type.get_game_info(), batch_config.bot_config.clone());
+    (0..num_games)
+        .map(|_| run_one_game(&batch_config, false, &game_factory, &bot_factory).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_external_bot() {
+    // The command records each time it is started, to check that the process is reused.
+    let log = std::env::temp_dir().join(format!("naughts-external-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&log);
+    let command = format!(
+        "echo started >> {}; exec python3 scripts/external_bot.py 2>/dev/null",
+        log.display()
+    );
+
+    for result in play_external_games(&command, 3, 0, TimeoutAction::Forfeit) {
+        assert!(result.is_win() || result.is_tie(), "{}", result);
+        assert_eq!(result.get_illegal_moves(), [0, 0]);
+        assert_eq!(result.get_think_times()[0].timeouts, 0);
+    }
+    let starts = std::fs::read_to_string(&log).unwrap();
+    let _ = std::fs::remove_file(&log);
+    assert_eq!(starts.lines().count(), 1, "One process played every game");
+}
+
+#[test]
+fn test_external_bot_timeout() {
+    // Reads the messages, but never answers.
+    let command = "cat > /dev/null";
+    let results = play_external_games(command, 1, 50, TimeoutAction::RandomMove);
+    assert!(results[0].is_win() || results[0].is_tie(), "{}", results[0]);
+    assert!(results[0].get_think_times()[0].timeouts > 0);
+
+    let results = play_external_games(command, 1, 50, TimeoutAction::Forfeit);
+    assert!(results[0].is_forfeit());
+    assert_eq!(results[0].get_winner_identity(), Some('O'));
+    assert_eq!(results[0].get_think_times()[0].timeouts, 1);
+}
+
+#[test]
+fn test_one_game_every_game_type() {
+    for spec in get_games() {
+        let game_type: GameType = spec.name.parse().unwrap();
+        let batch_config = get_batch_config(game_type.clone(), ["randombot", "randombot"], 1);
+        let game_factory = create_game_factory(&game_type);
+        let bot_factory = BotFactory::new(
+            game_factory().get_game_info(),
+            batch_config.bot_config.clone(),
+        );
+
+        let result = run_one_game(&batch_config, false, &game_factory, &bot_factory).unwrap();
+        assert!(
+            result.is_win() || result.is_tie(),
+            "{} did not finish: {}",
+            spec.name,
+            result
+        );
+        assert_eq!(result.get_illegal_moves(), [0, 0]);
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!("chess".parse::<GameType>().is_err());
+    assert!("nosuchbot".parse::<BotType>().is_err());
+    assert!("external:".parse::<BotType>().is_err());
+    assert!("randombot:x".parse::<BotType>().is_err());
+
+    let human: BotType = "hex.human".parse().unwrap();
+    assert!(human.check_game(&"hex".parse().unwrap()).is_ok());
+    assert!(human.check_game(&"naughts".parse().unwrap()).is_err());
+}
+
+#[test]
+fn test_batch() {
+    let game_type: GameType = "connect4".parse().unwrap();
+    let batch_config = get_batch_config(game_type.clone(), ["omnibot", "randombot"], 10);
+    let game_factory = create_game_factory(&game_type);
+    let bot_factory = BotFactory::new(
+        game_factory().get_game_info(),
+        batch_config.bot_config.clone(),
+    );
+
+    let result = run_batch(&batch_config, false, &game_factory, &bot_factory).unwrap();
+    assert!(result.is_batch());
+    assert_eq!(result.get_illegal_moves(), [0, 0]);
+    assert!(result.get_think_times()[0].mean() > std::time::Duration::from_secs(0));
+}
+
+#[test]
+fn test_genetic_bot_recipe_roundtrip() {
+    let game_type: GameType = "naughts".parse().unwrap();
+    let game_factory = create_game_factory(&game_type);
+    let batch_config = get_batch_config(game_type.clone(), ["genbot3", "randombot"], 1);
+    let bot_factory = BotFactory::new(
+        game_factory().get_game_info(),
+        batch_config.bot_config.clone(),
+    );
+
+    let genbot3: BotType = "genbot3".parse().unwrap();
+    let mut bot = bot_factory.create_bot(&genbot3);
+    assert!(bot.is_genetic());
+    bot.mutate();
+    let recipe = bot.to_json();
+    let copy = bot_factory
+        .create_bot_with_custom_recipe(&genbot3, &recipe)
+        .unwrap();
+    assert_eq!(copy.to_json(), recipe);
+
+    let game_info = game_factory().get_game_info();
+    let saved = RecipeEnvelope::new(&genbot3, &game_type, &game_info, recipe);
+    let loaded = RecipeEnvelope::from_json(&saved.to_json()).unwrap();
+    loaded.check(&genbot3, &game_type, &game_info).unwrap();
+    let connect4: GameType = "connect4".parse().unwrap();
+    assert!(loaded
+        .check(&genbot3, &connect4, &connect4.get_game_info())
+        .is_err());
+    assert_eq!(loaded, saved);
+
+    let bad_recipe = serde_json::json!({ "recipe": "NODE_INPUT,NODE_BAD:0" });
+    let err = bot_factory
+        .create_bot_with_custom_recipe(&genbot3, &bad_recipe)
+        .err()
+        .unwrap();
+    assert!(matches!(err, NaughtsError::RecipeParse(_)), "{}", err);
+}
+
+#[test]
+fn test_register_bot() {
+    let spec = BotSpec::new("test.randombot", "Random bot added by a test", |x| {
+        Box::new(RandomBot::new(x.game_info))
+    })
+    .with_games(&["naughts"]);
+    register_bot(spec.clone()).unwrap();
+    assert!(register_bot(spec).is_err(), "Bot names are unique");
+
+    let bot_type: BotType = "test.randombot".parse().unwrap();
+    assert!(bot_type.check_game(&"connect4".parse().unwrap()).is_err());
+
+    let game_type: GameType = "naughts".parse().unwrap();
+    let batch_config = get_batch_config(game_type.clone(), ["test.randombot", "randombot"], 1);
+    let game_factory = create_game_factory(&game_type);
+    let bot_factory = BotFactory::new(
+        game_factory().get_game_info(),
+        batch_config.bot_config.clone(),
+    );
+    let result = run_one_game(&batch_config, false, &game_factory, &bot_factory).unwrap();
+    assert!(result.is_win() || result.is_tie());
+}
+
+#[test]
+fn test_game_options() {
+    let mut hex: GameType = "hex".parse().unwrap();
+    let default_info = hex.get_game_info();
+    hex.set_option("size", serde_json::json!(5)).unwrap();
+    assert_eq!(hex.get_game_info().input_count, 50);
+    assert_ne!(hex.get_game_info(), default_info);
+
+    assert!(hex.set_option("size", serde_json::json!(0)).is_err());
+    assert!(hex.set_option("size", serde_json::json!("big")).is_err());
+    assert!(hex.set_option("heaps", serde_json::json!([1])).is_err());
+    assert_eq!(hex.get_options()["size"], 5, "Invalid options are not kept");
+
+    let mut nim: GameType = "nim".parse().unwrap();
+    assert!(nim.set_option("heaps", serde_json::json!([0, 0])).is_err());
+    nim.set_option("heaps", serde_json::json!([1, 2])).unwrap();
+
+    let mut kalah: GameType = "kalah".parse().unwrap();
+    kalah.set_option("pits", serde_json::json!(4)).unwrap();
+    kalah.set_option("seeds", serde_json::json!(3)).unwrap();
+    assert_eq!(kalah.get_game_info().input_count, 10);
+    assert_eq!(kalah.get_game_info().output_count, 4);
+    assert!(kalah.set_option("seeds", serde_json::json!(0)).is_err());
+
+    let mut checkers: GameType = "checkers".parse().unwrap();
+    checkers
+        .set_option("draw_limit", serde_json::json!(40))
+        .unwrap();
+    assert!(checkers
+        .set_option("draw_limit", serde_json::json!(0))
+        .is_err());
+}
+
+#[test]
+fn test_register_game() {
+    let spec =
+        GameSpec::without_options("test.naughts", "Naughts added by a test", NaughtsGame::new);
+    assert_eq!(spec.identities, ['X', 'O']);
+    register_game(spec.clone()).unwrap();
+    assert!(register_game(spec).is_err(), "Game names are unique");
+
+    let game_type: GameType = "test.naughts".parse().unwrap();
+    let batch_config = get_batch_config(game_type.clone(), ["randombot", "randombot"], 1);
+    let game_factory = create_game_factory(&game_type);
+    let bot_factory = BotFactory::new(game_type.get_game_info(), batch_config.bot_config.clone());
+    let result = run_one_game(&batch_config, false, &game_factory, &bot_factory).unwrap();
+    assert!(result.is_win() || result.is_tie());
+}